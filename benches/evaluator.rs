@@ -8,7 +8,7 @@ use test::Bencher;
 use pokerlib::cards_type::CardsType;
 use pokerlib::card::Card;
 use std::path::Path;
-use pokerlib::Evaluator;
+use pokerlib::{Evaluator, IncrementalEvaluator};
 use pokerlib::{NativeEvaluator, CactusKevEvaluator, TwoPlusTwoEvaluator};
 use pokerlib::tools::combination::CombinationIter;
 use pokerlib::tools::twoplustwo::generate_data_file;
@@ -22,7 +22,7 @@ lazy_static! {
         if !Path::new("TptHandRank.dat").exists() {
             generate_data_file(Path::new("TptHandRank.dat")).ok().unwrap();
         }
-        TwoPlusTwoEvaluator::with_data_file("TptHandRank.dat")
+        TwoPlusTwoEvaluator::from_file("TptHandRank.dat").unwrap()
     };
 }
 
@@ -80,7 +80,8 @@ pub fn get_test_cards_for_type<T: Copy,E: Evaluator<CardType=T>>(
         CardsType::Straight => vec!["As","Kh","Jd","Qc","Th","4d","9s"],
 //        CardsType::StraightFlush => vec!["2s","Ks","Js","Qs","Ts","4d","9s"],
         CardsType::StraightFlush => vec!["As","Ks","Js","Qs","Ts","4d","9s"],
-    }.iter().map(|&x| evaluator.make_card(&Card::from_str(x).ok().unwrap())).collect()
+        _ => panic!("no such cards type without wild cards"),
+    }.iter().map(|&x| evaluator.make_card(&x.parse::<Card>().unwrap())).collect()
 }
 
 macro_rules! fix_cards_type {
@@ -164,3 +165,93 @@ select_cards_all!(twoplustwo_select_6_all, TWOPLUSTWO_EVALUATOR, 6);
 select_cards_some!(native_select_7_some, NATIVE_EVALUATOR, 7, 1_000_000);
 select_cards_some!(cactuskev_select_7_some, CACTUSKEV_EVALUATOR, 7, 1_000_000);
 select_cards_some!(twoplustwo_select_7_some, TWOPLUSTWO_EVALUATOR, 7, 1_000_000);
+
+macro_rules! select_cards_batch {
+    ($name:ident, $evaluator:ident, $count:expr, $somecount:expr) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let all_test_cards: Vec<_> = all_combination_cards_ex(&*$evaluator, $count, None, Some($somecount))
+                .concat();
+            let mut out = vec![0; $somecount];
+            b.iter(||{
+                $evaluator.eval_batch(&all_test_cards, $count, &mut out);
+            })
+        }
+    };
+}
+
+// 随机的 7 张牌, 一手接一手平铺
+fn random_hands<T: Copy, E: Evaluator<CardType=T>>(evaluator: &E, count: usize) -> Vec<T> {
+    use pokerlib::deck::Deck;
+    use pokerlib::rng::SeededRng;
+
+    let mut rng = SeededRng::new(20);
+    (0..count).flat_map(|_| {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut rng);
+        deck.deal(7).unwrap()
+    }).map(|c| evaluator.make_card(&c)).collect()
+}
+
+// eval_batch 和 trait 默认实现 (逐手 eval) 的对比
+#[bench]
+fn native_random_7_batch(b: &mut Bencher) {
+    let cards = random_hands(&*NATIVE_EVALUATOR, 100_000);
+    let mut out = vec![0; 100_000];
+    b.iter(|| NATIVE_EVALUATOR.eval_batch(&cards, 7, &mut out));
+}
+
+#[bench]
+fn native_random_7_scalar(b: &mut Bencher) {
+    let cards = random_hands(&*NATIVE_EVALUATOR, 100_000);
+    let mut out = vec![0; 100_000];
+    b.iter(|| {
+        for (hand, value) in cards.chunks_exact(7).zip(out.iter_mut()) {
+            *value = NATIVE_EVALUATOR.eval(hand);
+        }
+    });
+}
+
+select_cards_some!(native_select_7_per_hand, NATIVE_EVALUATOR, 7, 100_000);
+select_cards_batch!(native_select_7_batch, NATIVE_EVALUATOR, 7, 100_000);
+select_cards_some!(cactuskev_select_7_per_hand, CACTUSKEV_EVALUATOR, 7, 100_000);
+select_cards_batch!(cactuskev_select_7_batch, CACTUSKEV_EVALUATOR, 7, 100_000);
+select_cards_some!(twoplustwo_select_7_per_hand, TWOPLUSTWO_EVALUATOR, 7, 100_000);
+select_cards_batch!(twoplustwo_select_7_batch, TWOPLUSTWO_EVALUATOR, 7, 100_000);
+
+// 固定 2 张手牌和翻牌, 枚举所有转牌和河牌
+macro_rules! runout_incremental {
+    ($name:ident, $full_name:ident, $evaluator:ident) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let cards: Vec<_> = Card::one_desk_cards().iter().map(|x| $evaluator.make_card(x)).collect();
+            b.iter(||{
+                let flop = cards[..5].iter().fold($evaluator.start(), |state, &c| $evaluator.add_card(state, c));
+                for i in 5..52 {
+                    let turn = $evaluator.add_card(flop, cards[i]);
+                    for j in i+1..52 {
+                        test::black_box($evaluator.finish($evaluator.add_card(turn, cards[j])));
+                    }
+                }
+            })
+        }
+
+        #[bench]
+        fn $full_name(b: &mut Bencher) {
+            let cards: Vec<_> = Card::one_desk_cards().iter().map(|x| $evaluator.make_card(x)).collect();
+            b.iter(||{
+                let mut hand = cards[..7].to_vec();
+                for i in 5..52 {
+                    hand[5] = cards[i];
+                    for j in i+1..52 {
+                        hand[6] = cards[j];
+                        test::black_box($evaluator.eval(&hand));
+                    }
+                }
+            })
+        }
+    };
+}
+
+runout_incremental!(native_runout_incremental, native_runout_eval, NATIVE_EVALUATOR);
+runout_incremental!(twoplustwo_runout_incremental, twoplustwo_runout_eval, TWOPLUSTWO_EVALUATOR);
@@ -0,0 +1,36 @@
+// feature `embedded`: 在编译期生成 twoplustwo 表格, 由 TwoPlusTwoEvaluator::embedded() 使用
+//
+// 只引入两个只依赖 std 的文件: cactuskev 查表求值和 twoplustwo 表格生成器
+#[cfg(feature = "embedded")]
+#[path = "src/evaluator/cactuskev/lookup.rs"]
+mod cactuskev;
+#[cfg(feature = "embedded")]
+#[path = "src/tools/twoplustwo/generator.rs"]
+mod generator;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "embedded")]
+    generate_table();
+}
+
+// 生成表格写到 OUT_DIR, 没开 feature 时这些代码都不编译
+#[cfg(feature = "embedded")]
+fn generate_table() {
+    use generator::{table_checksum, DATA_FILE_CHECKSUM, DATA_FILE_LEN};
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    for file in ["src/evaluator/cactuskev", "src/tools/twoplustwo/generator.rs"].iter() {
+        println!("cargo:rerun-if-changed={}", file);
+    }
+
+    let table = generator::generate_table(&cactuskev::eval_cards);
+    assert_eq!(table.len(), DATA_FILE_LEN);
+    assert_eq!(table_checksum(&table), DATA_FILE_CHECKSUM, "generated twoplustwo table checksum mismatch");
+
+    let bytes: Vec<u8> = table.iter().flat_map(|entry| entry.to_le_bytes()).collect();
+    let path = Path::new(&env::var_os("OUT_DIR").unwrap()).join("TptHandRank.dat");
+    fs::write(path, bytes).unwrap();
+}
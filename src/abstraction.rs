@@ -0,0 +1,380 @@
+use crate::Evaluator;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::deck::Street;
+use crate::equity::{EquityError, used_cards};
+use crate::hand_indexer::HandIndexer;
+use crate::rng::{Rng, SeededRng};
+use crate::tools::combination::CombinationIter;
+
+/// bin of an equity in `0..bins`, equity 1 is in the last bin
+#[inline]
+pub fn equity_bin(equity: f64, bins: usize) -> usize {
+    ((equity * bins as f64) as usize).min(bins - 1)
+}
+
+/// Equity distribution histogram of hole cards on a flop, turn or river
+///
+/// Every runout to the river is enumerated, the river equity (win + tie/2) against a random hand
+/// (card removal is exact) is counted in `bins` equal bins over `0..=1`, normalized to sum 1.
+/// On the river it has a single nonzero bin. Histograms are f32 everywhere in this module,
+/// the street tables of `tools::abstraction` are large.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, NativeEvaluator};
+/// use pokerlib::abstraction::equity_histogram;
+///
+/// let hole: Vec<Card> = parse_cards("AsKs").unwrap();
+/// let board: Vec<Card> = parse_cards("Qs7s2dJh").unwrap();
+/// let histogram = equity_histogram(&NativeEvaluator::new(), [hole[0], hole[1]], &board, 10).unwrap();
+/// assert_eq!(histogram.len(), 10);
+/// assert!((histogram.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+/// ```
+pub fn equity_histogram<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hole: [Card;2],
+    board: &[Card],
+    bins: usize,
+) -> Result<Vec<f32>, EquityError> {
+    assert!(bins > 0, "need at least one bin");
+    if board.len() < 3 || board.len() > 5 {
+        return Err(EquityError::InvalidBoard(board.len()));
+    }
+    let used = used_cards(hole.iter().chain(board.iter()), CardSet::new())?;
+    let deck = (!used).to_vec();
+    let missing = 5 - board.len();
+    let mut ours = [evaluator.make_card(&hole[0]);7];
+    let mut theirs = ours;
+    ours[1] = evaluator.make_card(&hole[1]);
+    for (i, card) in board.iter().enumerate() {
+        ours[2+i] = evaluator.make_card(card);
+        theirs[2+i] = ours[2+i];
+    }
+
+    let mut counts = vec![0u32; bins];
+    let mut runouts = 0u32;
+    let mut it = CombinationIter::new(deck.len(), missing);
+    let mut indexes = [0;2];
+    let mut live = Vec::with_capacity(deck.len());
+    loop {
+        if missing > 0 && !it.move_next(&mut indexes) {
+            break;
+        }
+        let runout = &indexes[..missing];
+        for (k, &i) in runout.iter().enumerate() {
+            ours[7-missing+k] = evaluator.make_card(&deck[i]);
+            theirs[7-missing+k] = ours[7-missing+k];
+        }
+        live.clear();
+        live.extend(deck.iter().enumerate().filter(|(i, _)| !runout.contains(i)).map(|(_, &c)| c));
+        let value = evaluator.eval(&ours);
+        let (mut wins, mut ties, mut total) = (0u32, 0u32, 0u32);
+        let mut opponents = CombinationIter::new(live.len(), 2);
+        let mut pair = [0;2];
+        while opponents.move_next(&mut pair) {
+            theirs[0] = evaluator.make_card(&live[pair[0]]);
+            theirs[1] = evaluator.make_card(&live[pair[1]]);
+            let other = evaluator.eval(&theirs);
+            if value > other {
+                wins += 1;
+            }else if value == other {
+                ties += 1;
+            }
+            total += 1;
+        }
+        counts[equity_bin((wins as f64 + ties as f64 / 2.0) / total as f64, bins)] += 1;
+        runouts += 1;
+        if missing == 0 {
+            break;
+        }
+    }
+    Ok(counts.iter().map(|&n| n as f32 / runouts as f32).collect())
+}
+
+/// Earth mover's distance of two histograms with same bins
+///
+/// In one dimension it's the L1 distance of the cumulative sums, in bin widths.
+pub fn emd(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len(), "histograms should have same bins");
+    let mut distance = 0.0;
+    let mut carry = 0.0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        carry += x - y;
+        distance += carry.abs();
+    }
+    distance
+}
+
+/// Result of `kmeans_emd`
+#[derive(Debug, Clone)]
+pub struct Clusters {
+    /// bins count of a histogram
+    pub bins: usize,
+    /// k centroid histograms, flattened
+    pub centroids: Vec<f32>,
+    /// cluster of every histogram
+    pub assignments: Vec<u16>,
+}
+
+// L1 距离, 超过 bound 时提前返回
+#[inline]
+fn l1_distance(a: &[f32], b: &[f32], bound: f32) -> f32 {
+    let mut distance = 0.0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        distance += (x - y).abs();
+        if distance >= bound {
+            break;
+        }
+    }
+    distance
+}
+
+/// k-means clustering of histograms by earth mover's distance
+///
+/// `histograms` are flattened, `bins` values each, normalized. Centroids are initialized by
+/// k-means++ with a seeded rng, then updated as the mean histogram for at most `iterations` rounds,
+/// every histogram is assigned to its nearest returned centroid.
+/// k is reduced to the histogram count if larger.
+///
+/// Example:
+/// ```
+/// use pokerlib::abstraction::kmeans_emd;
+///
+/// let histograms = [1.0, 0.0, 0.0,  0.9, 0.1, 0.0,  0.0, 0.0, 1.0,  0.0, 0.2, 0.8];
+/// let clusters = kmeans_emd(&histograms, 3, 2, 10, 42);
+/// assert_eq!(clusters.assignments[0], clusters.assignments[1]);
+/// assert_eq!(clusters.assignments[2], clusters.assignments[3]);
+/// assert_ne!(clusters.assignments[0], clusters.assignments[2]);
+/// ```
+pub fn kmeans_emd(histograms: &[f32], bins: usize, k: usize, iterations: usize, seed: u64) -> Clusters {
+    weighted_kmeans_emd(histograms.to_vec(), None, bins, k, iterations, seed)
+}
+
+// 带权重的 k-means, 直接在 histograms 上转换成累计分布, 不复制
+pub(crate) fn weighted_kmeans_emd(
+    histograms: Vec<f32>, weights: Option<&[f64]>, bins: usize, k: usize, iterations: usize, seed: u64,
+) -> Clusters {
+    assert!(bins > 0 && histograms.len() % bins == 0, "histograms should be flattened by bins");
+    assert!(k > 0 && k <= u16::MAX as usize + 1, "k should be in 1..=65536");
+    // 一维的 EMD 就是累计分布的 L1 距离, 均值的累计分布也是累计分布的均值
+    let mut cdfs = histograms;
+    for cdf in cdfs.chunks_exact_mut(bins) {
+        for i in 1..bins {
+            cdf[i] += cdf[i-1];
+        }
+    }
+    let n = cdfs.len() / bins;
+    let k = k.min(n);
+    if n == 0 {
+        return Clusters{bins, centroids: vec![], assignments: vec![]};
+    }
+    let point = |i: usize| &cdfs[i*bins..(i+1)*bins];
+    let weight = |i: usize| weights.map_or(1.0, |w| w[i]);
+
+    // k-means++, 按 weight * D² 的比例选下一个中心
+    let mut rng = SeededRng::new(seed);
+    let sample = |rng: &mut SeededRng, score: &dyn Fn(usize) -> f64| {
+        let sum: f64 = (0..n).map(score).sum();
+        if sum <= 0.0 {
+            return rng.gen_range(n as u64) as usize;
+        }
+        let mut target = rng.gen_f64() * sum;
+        for i in 0..n {
+            target -= score(i);
+            if target < 0.0 {
+                return i;
+            }
+        }
+        n - 1
+    };
+    let mut centroids = point(sample(&mut rng, &weight)).to_vec();
+    let mut nearest: Vec<f32> = (0..n).map(|i| l1_distance(point(i), &centroids, f32::MAX)).collect();
+    while centroids.len() < k * bins {
+        let chosen = sample(&mut rng, &|i| weight(i) * nearest[i] as f64 * nearest[i] as f64);
+        let start = centroids.len();
+        centroids.extend_from_slice(point(chosen));
+        for (i, d) in nearest.iter_mut().enumerate() {
+            *d = d.min(l1_distance(point(i), &centroids[start..], *d));
+        }
+    }
+
+    // 每个点分到最近的中心, 返回是否有变化
+    let assign = |centroids: &[f32], assignments: &mut [u16]| {
+        let mut changed = false;
+        for (i, assignment) in assignments.iter_mut().enumerate() {
+            let mut best = (*assignment as usize, l1_distance(point(i), &centroids[*assignment as usize * bins..][..bins], f32::MAX));
+            for c in 0..k {
+                let distance = l1_distance(point(i), &centroids[c*bins..(c+1)*bins], best.1);
+                if distance < best.1 {
+                    best = (c, distance);
+                }
+            }
+            if best.0 != *assignment as usize {
+                *assignment = best.0 as u16;
+                changed = true;
+            }
+        }
+        changed
+    };
+
+    // 每轮先更新中心再重新分配, 返回的分配总是对应返回的中心
+    let mut assignments = vec![0u16; n];
+    assign(&centroids, &mut assignments);
+    for _ in 0..iterations {
+        let mut sums = vec![0.0f64; k * bins];
+        let mut counts = vec![0.0f64; k];
+        for (i, &assignment) in assignments.iter().enumerate() {
+            let c = assignment as usize;
+            counts[c] += weight(i);
+            for (s, &x) in sums[c*bins..(c+1)*bins].iter_mut().zip(point(i)) {
+                *s += x as f64 * weight(i);
+            }
+        }
+        // 空的类保留原来的中心
+        for c in 0..k {
+            if counts[c] > 0.0 {
+                for (centroid, &s) in centroids[c*bins..(c+1)*bins].iter_mut().zip(&sums[c*bins..(c+1)*bins]) {
+                    *centroid = (s / counts[c]) as f32;
+                }
+            }
+        }
+        if !assign(&centroids, &mut assignments) {
+            break;
+        }
+    }
+
+    for centroid in centroids.chunks_exact_mut(bins) {
+        for i in (1..bins).rev() {
+            centroid[i] -= centroid[i-1];
+        }
+    }
+    Clusters{bins, centroids, assignments}
+}
+
+/// Bucket assignments of all hands of a street
+///
+/// Hands are indexed by `HandIndexer::hold_em_street`, so suit isomorphic hands share a bucket.
+/// Build it with `tools::abstraction::build_abstraction`, or load saved buckets by `from_buckets`.
+///
+/// Example:
+/// ```no_run
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::deck::Street;
+/// use pokerlib::tools::abstraction::{build_abstraction, AbstractionOptions};
+///
+/// let evaluator = pokerlib::TwoPlusTwoEvaluator::from_file("TptHandRank.dat").unwrap();
+/// let flop = build_abstraction(&evaluator, Street::Flop, &AbstractionOptions::default());
+/// let hole = parse_cards::<Vec<_>>("AsKs").unwrap();
+/// let board = parse_cards::<Vec<_>>("Qs7s2d").unwrap();
+/// let bucket = flop.bucket([hole[0], hole[1]], &board).unwrap();
+/// ```
+pub struct CardAbstraction {
+    street: Street,
+    indexer: HandIndexer,
+    bucket_count: usize,
+    buckets: Vec<u16>,
+}
+
+impl CardAbstraction {
+    /// make abstraction from the bucket of every hand index, None if the count is wrong
+    /// or a bucket is not less than `bucket_count`
+    pub fn from_buckets(street: Street, bucket_count: usize, buckets: Vec<u16>) -> Option<CardAbstraction> {
+        let indexer = HandIndexer::hold_em_street(street);
+        if buckets.len() as u64 != indexer.size(indexer.rounds() - 1)
+            || buckets.iter().any(|&b| b as usize >= bucket_count) {
+            return None;
+        }
+        Some(CardAbstraction{street, indexer, bucket_count, buckets})
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        self.bucket_count
+    }
+
+    /// bucket of every hand index
+    pub fn buckets(&self) -> &[u16] {
+        &self.buckets
+    }
+
+    /// bucket of the hand, None if the board doesn't match the street or cards are invalid
+    pub fn bucket(&self, hole: [Card;2], board: &[Card]) -> Option<usize> {
+        if board.len() != self.street.board_len() {
+            return None;
+        }
+        let mut cards = [hole[0];7];
+        cards[1] = hole[1];
+        cards[2..2 + board.len()].copy_from_slice(board);
+        self.indexer.index(&cards[..2 + board.len()]).map(|index| self.buckets[index as usize] as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NativeEvaluator;
+    use crate::card::parse_cards;
+
+    #[test]
+    fn test_equity_histogram(){
+        let evaluator = NativeEvaluator::new();
+        let hole: Vec<Card> = parse_cards("AsAd").unwrap();
+        let board: Vec<Card> = parse_cards("Ah7d2cKs9h").unwrap();
+        let river = equity_histogram(&evaluator, [hole[0], hole[1]], &board, 5).unwrap();
+        assert_eq!(river, vec![0.0, 0.0, 0.0, 0.0, 1.0]);
+
+        // 每个转牌的河牌胜率和 exact_equity 对 random 的一致
+        let board: Vec<Card> = parse_cards("Qs7s2dJh").unwrap();
+        let turn = equity_histogram(&evaluator, [hole[0], hole[1]], &board, 20).unwrap();
+        let mut expected = vec![0.0f32; 20];
+        for card in (!CardSet::from(&[hole[0], hole[1]][..]) - board[..].into()).iter() {
+            let mut river = board.clone();
+            river.push(card);
+            let histogram = equity_histogram(&evaluator, [hole[0], hole[1]], &river, 20).unwrap();
+            for (e, h) in expected.iter_mut().zip(histogram) {
+                *e += h / 46.0;
+            }
+        }
+        assert!(turn.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-6));
+        assert_eq!(equity_histogram(&evaluator, [hole[0], hole[1]], &board[..2], 20), Err(EquityError::InvalidBoard(2)));
+        assert_eq!(equity_histogram(&evaluator, [hole[0], Card::JOKER], &board, 20), Err(EquityError::InvalidCard(Card::JOKER)));
+        assert_eq!(equity_histogram(&evaluator, [hole[0], board[0]], &board, 20), Err(EquityError::DuplicateCard(board[0])));
+    }
+
+    #[test]
+    fn test_emd_kmeans(){
+        assert_eq!(emd(&[1.0, 0.0, 0.0], &[0.0, 0.0, 1.0]), 2.0);
+        assert_eq!(emd(&[0.5, 0.5, 0.0], &[0.0, 0.5, 0.5]), 1.0);
+
+        // 两个 bin 距离相同, 但 EMD 区分远近
+        let histograms = [1.0, 0.0, 0.0, 0.0,  0.0, 1.0, 0.0, 0.0,  0.0, 0.0, 0.0, 1.0];
+        let clusters = kmeans_emd(&histograms, 4, 2, 10, 1);
+        assert_eq!(clusters.assignments[0], clusters.assignments[1]);
+        assert_ne!(clusters.assignments[0], clusters.assignments[2]);
+        let c = clusters.assignments[0] as usize * 4;
+        assert_eq!(&clusters.centroids[c..c+4], &[0.5, 0.5, 0.0, 0.0]);
+        assert_eq!(kmeans_emd(&histograms, 4, 10, 10, 1).centroids.len(), 12);
+
+        // 迭代次数用完时, 每个点也分在最近的中心
+        let mut rng = SeededRng::new(5);
+        let histograms: Vec<f32> = (0..200).flat_map(|_| {
+            let h: Vec<f64> = (0..5).map(|_| rng.gen_f64()).collect();
+            let sum: f64 = h.iter().sum();
+            h.into_iter().map(move |x| (x / sum) as f32)
+        }).collect();
+        for iterations in 0..3 {
+            let clusters = kmeans_emd(&histograms, 5, 6, iterations, 2);
+            let centroids: Vec<&[f32]> = clusters.centroids.chunks(5).collect();
+            for (i, &assignment) in clusters.assignments.iter().enumerate() {
+                let point = &histograms[i*5..(i+1)*5];
+                let nearest = centroids.iter().map(|c| emd(point, c)).fold(f32::MAX, f32::min);
+                assert!(emd(point, centroids[assignment as usize]) <= nearest + 1e-5);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use std::error::Error;
 use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
 
 /// Cards' value enum, 23456789TJQKA
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
@@ -23,7 +26,7 @@ const CARD_VALUES: [Value;13] = [
     Value::Eight, Value::Nign, Value::Ten, Value::Jack, Value::Queen, Value::Kine, Value::Ace];
 
 impl Value {
-    pub fn to_string(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match *self {
             Value::Two => "2",
             Value::Three => "3",
@@ -41,8 +44,16 @@ impl Value {
         }
     }
 
+    /// same as `as_str`, kept for compatibility
+    #[deprecated(note = "use `as_str`, `to_string` will be the one of `Display`")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// case-insensitive: `'t'` and `'T'` are both Ten
     pub fn from_char(c: char) -> Result<Value, CardStringConvertError> {
-        match c {
+        match c.to_ascii_uppercase() {
             '2' => Ok(Value::Two),
             '3' => Ok(Value::Three),
             '4' => Ok(Value::Four),
@@ -56,7 +67,7 @@ impl Value {
             'Q' => Ok(Value::Queen),
             'K' => Ok(Value::Kine),
             'A' => Ok(Value::Ace),
-            _ => Err(CardStringConvertError)
+            _ => Err(CardStringConvertError::InvalidValue{ch: c, position: 0})
         }
     }
 
@@ -80,7 +91,7 @@ pub enum Suit {
 const CARD_SUITS: [Suit;4] = [Suit::Spade, Suit::Heart, Suit::Club, Suit::Diamond];
 
 impl Suit {
-    pub fn to_string(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match *self {
             Suit::Spade => "s",
             Suit::Heart => "h",
@@ -88,20 +99,42 @@ impl Suit {
             Suit::Diamond => "d",
         }
     }
+
+    /// same as `as_str`, kept for compatibility
+    #[deprecated(note = "use `as_str`, `to_string` will be the one of `Display`")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// case-insensitive, unicode suit symbols (♠♥♣♦, ♤♡♧♢) are accepted
     pub fn from_char(c: char) -> Result<Suit, CardStringConvertError>{
         match c {
-            's' => Ok(Suit::Spade),
-            'h' => Ok(Suit::Heart),
-            'c' => Ok(Suit::Club),
-            'd' => Ok(Suit::Diamond),
-            _ => Err(CardStringConvertError)
+            's' | 'S' | '♠' | '♤' => Ok(Suit::Spade),
+            'h' | 'H' | '♥' | '♡' => Ok(Suit::Heart),
+            'c' | 'C' | '♣' | '♧' => Ok(Suit::Club),
+            'd' | 'D' | '♦' | '♢' => Ok(Suit::Diamond),
+            _ => Err(CardStringConvertError::InvalidSuit{ch: c, position: 0})
         }
     }
+
+    pub fn from_index(index: u8) -> Suit {
+        CARD_SUITS[index as usize]
+    }
+
+    pub fn index(&self) -> u8 {
+        (0..4).find(|&x| CARD_SUITS[x] == *self).unwrap() as u8
+    }
 }
 
+/// A card of the 52, or a joker
+///
+/// A joker has no value and suit: `value` panics on it, `value_index`, `suit` and
+/// `suit_index` give meaningless results, check `is_joker` first. Only `WildEvaluator`
+/// evaluates jokers.
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
 pub struct Card{
-    card_index: u8, // policy: GroupByValue
+    card_index: u8, // policy: GroupByValue, 52 is joker
 }
 
 /// Card's index policy definition.
@@ -139,9 +172,69 @@ pub enum CardIndexPolicy {
     GroupBySuit,
 }
 
-pub struct CardStringConvertError;
+/// Error of converting string to cards
+///
+/// position is the char (not byte) position in the input string, starts from 0.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum CardStringConvertError {
+    /// nothing to parse
+    Empty,
+    /// not a card value: 23456789TJQKA
+    InvalidValue{ch: char, position: usize},
+    /// not a card suit: shcd
+    InvalidSuit{ch: char, position: usize},
+    /// input ends after a card value
+    MissingSuit{position: usize},
+    /// unexpected char after a card
+    UnexpectedChar{ch: char, position: usize},
+    /// a card appears more than once
+    DuplicateCard{card: Card, position: usize},
+}
+
+impl CardStringConvertError {
+    // move the error position by offset
+    fn offset(self, offset: usize) -> CardStringConvertError {
+        match self {
+            CardStringConvertError::InvalidValue{ch, position} =>
+                CardStringConvertError::InvalidValue{ch, position: position + offset},
+            CardStringConvertError::InvalidSuit{ch, position} =>
+                CardStringConvertError::InvalidSuit{ch, position: position + offset},
+            CardStringConvertError::MissingSuit{position} =>
+                CardStringConvertError::MissingSuit{position: position + offset},
+            CardStringConvertError::UnexpectedChar{ch, position} =>
+                CardStringConvertError::UnexpectedChar{ch, position: position + offset},
+            CardStringConvertError::DuplicateCard{card, position} =>
+                CardStringConvertError::DuplicateCard{card, position: position + offset},
+            CardStringConvertError::Empty => CardStringConvertError::Empty,
+        }
+    }
+}
+
+impl fmt::Display for CardStringConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardStringConvertError::Empty =>
+                write!(f, "no card found"),
+            CardStringConvertError::InvalidValue{ch, position} =>
+                write!(f, "invalid card value '{}' at position {}", ch, position),
+            CardStringConvertError::InvalidSuit{ch, position} =>
+                write!(f, "invalid card suit '{}' at position {}", ch, position),
+            CardStringConvertError::MissingSuit{position} =>
+                write!(f, "missing card suit at position {}", position),
+            CardStringConvertError::UnexpectedChar{ch, position} =>
+                write!(f, "unexpected '{}' at position {}", ch, position),
+            CardStringConvertError::DuplicateCard{card, position} =>
+                write!(f, "duplicate card {} at position {}", card, position),
+        }
+    }
+}
+
+impl Error for CardStringConvertError {}
 
 impl Card {
+    /// the joker, its card index is 52 with any policy
+    pub const JOKER: Card = Card{card_index: 52};
+
     pub fn new(value: Value, suit: Suit)-> Card{
         let v_idx = (0..13).find(|&x| CARD_VALUES[x] == value).unwrap();
         let s_idx = (0..4).find(|&x| CARD_SUITS[x] == suit).unwrap();
@@ -157,19 +250,27 @@ impl Card {
         Card{card_index: value_index * 4 + suit_index}
     }
 
+    /// 0-51 by policy, 52 is joker
     pub fn with_card_index(card_index: u8, policy: CardIndexPolicy) -> Card{
-        if card_index >= 52 {
+        if card_index > 52 {
             panic!("Card::with_card_index argument error: {}", card_index);
         }
         Card{card_index: match policy {
+            _ if card_index == 52 => 52,
             CardIndexPolicy::GroupByValue => card_index,
             CardIndexPolicy::GroupBySuit => (card_index%13) * 4 + (card_index/13),
         }}
     }
 
+    #[inline]
+    pub fn is_joker(&self) -> bool {
+        self.card_index == 52
+    }
+
+    /// the card's value, panics on the joker
     #[inline]
     pub fn value(&self) -> Value {
-        CARD_VALUES[self.card_index as usize/4]
+        *CARD_VALUES.get(self.card_index as usize/4).expect("the joker has no value")
     }
 
     #[inline]
@@ -191,34 +292,179 @@ impl Card {
     pub fn card_index(&self, policy: CardIndexPolicy) -> u8 {
         match policy {
             CardIndexPolicy::GroupByValue => self.card_index,
+            CardIndexPolicy::GroupBySuit if self.is_joker() => 52,
             CardIndexPolicy::GroupBySuit => self.suit_index() * 13 + self.value_index()
         }
     }
 
-    pub fn to_string(&self) -> String {
-        let mut s = String::from("");
-        s.push_str(self.value().to_string());
-        s.push_str(self.suit().to_string());
-        s
+    pub fn one_desk_cards() -> [Card;52] {
+        [Card{card_index:0},Card{card_index:1},Card{card_index:2},Card{card_index:3},Card{card_index:4},Card{card_index:5},Card{card_index:6},Card{card_index:7},Card{card_index:8},Card{card_index:9},Card{card_index:10},Card{card_index:11},Card{card_index:12},Card{card_index:13},Card{card_index:14},Card{card_index:15},Card{card_index:16},Card{card_index:17},Card{card_index:18},Card{card_index:19},Card{card_index:20},Card{card_index:21},Card{card_index:22},Card{card_index:23},Card{card_index:24},Card{card_index:25},Card{card_index:26},Card{card_index:27},Card{card_index:28},Card{card_index:29},Card{card_index:30},Card{card_index:31},Card{card_index:32},Card{card_index:33},Card{card_index:34},Card{card_index:35},Card{card_index:36},Card{card_index:37},Card{card_index:38},Card{card_index:39},Card{card_index:40},Card{card_index:41},Card{card_index:42},Card{card_index:43},Card{card_index:44},Card{card_index:45},Card{card_index:46},Card{card_index:47},Card{card_index:48},Card{card_index:49},Card{card_index:50},Card{card_index:51}]
     }
+}
 
-    pub fn from_str(card_str: &str) -> Result<Card, CardStringConvertError>{
-        if card_str.len() < 2 {
-            return Err(CardStringConvertError);
-        }
-        let mut chars = card_str.chars();
-        let value = Value::from_char(chars.next().unwrap())?;
-        let suit = Suit::from_char(chars.next().unwrap())?;
-        Ok(Card::new(value, suit))
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
     }
+}
 
-    pub fn one_desk_cards() -> [Card;52] {
-        [Card{card_index:0},Card{card_index:1},Card{card_index:2},Card{card_index:3},Card{card_index:4},Card{card_index:5},Card{card_index:6},Card{card_index:7},Card{card_index:8},Card{card_index:9},Card{card_index:10},Card{card_index:11},Card{card_index:12},Card{card_index:13},Card{card_index:14},Card{card_index:15},Card{card_index:16},Card{card_index:17},Card{card_index:18},Card{card_index:19},Card{card_index:20},Card{card_index:21},Card{card_index:22},Card{card_index:23},Card{card_index:24},Card{card_index:25},Card{card_index:26},Card{card_index:27},Card{card_index:28},Card{card_index:29},Card{card_index:30},Card{card_index:31},Card{card_index:32},Card{card_index:33},Card{card_index:34},Card{card_index:35},Card{card_index:36},Card{card_index:37},Card{card_index:38},Card{card_index:39},Card{card_index:40},Card{card_index:41},Card{card_index:42},Card{card_index:43},Card{card_index:44},Card{card_index:45},Card{card_index:46},Card{card_index:47},Card{card_index:48},Card{card_index:49},Card{card_index:50},Card{card_index:51}]
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        if self.is_joker() {
+            return f.write_str("Jk");
+        }
+        write!(f, "{}{}", self.value(), self.suit())
+    }
+}
+
+impl FromStr for Value {
+    type Err = CardStringConvertError;
+
+    /// one value char or "10"
+    fn from_str(s: &str) -> Result<Value, CardStringConvertError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (None, _, _) => Err(CardStringConvertError::Empty),
+            (Some(c), None, _) => Value::from_char(c),
+            (Some('1'), Some('0'), None) => Ok(Value::Ten),
+            (Some(c), Some(_), _) if Value::from_char(c).is_err() => Value::from_char(c),
+            (Some(_), Some(c), _) => Err(CardStringConvertError::UnexpectedChar{ch: c, position: 1}),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = CardStringConvertError;
+
+    fn from_str(s: &str) -> Result<Suit, CardStringConvertError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => Err(CardStringConvertError::Empty),
+            (Some(c), None) => Suit::from_char(c),
+            (Some(_), Some(c)) => Err(CardStringConvertError::UnexpectedChar{ch: c, position: 1}),
+        }
+    }
+}
+
+impl FromStr for Card {
+    type Err = CardStringConvertError;
+
+    /// one card, e.g. "As", "td", "10h", "Q♠", "Jk" (joker)
+    fn from_str(s: &str) -> Result<Card, CardStringConvertError> {
+        let mut chars = s.trim_end().chars().enumerate().peekable();
+        match parse_next_card(&mut chars)? {
+            None => Err(CardStringConvertError::Empty),
+            Some((card, _)) => match chars.next() {
+                None => Ok(card),
+                Some((position, ch)) => Err(CardStringConvertError::UnexpectedChar{ch, position}),
+            }
+        }
+    }
+}
+
+// parse one card from chars, leading whitespace and commas are skipped
+fn parse_next_card<I>(chars: &mut std::iter::Peekable<I>) -> Result<Option<(Card, usize)>, CardStringConvertError>
+where I: Iterator<Item = (usize, char)> {
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        }else{
+            break;
+        }
+    }
+    let (start, c) = match chars.next() {
+        None => return Ok(None),
+        Some(x) => x,
+    };
+    let value = match (c, chars.peek()) {
+        ('🃏', _) => return Ok(Some((Card::JOKER, start))),
+        ('J', Some(&(_, 'k'))) | ('J', Some(&(_, 'K'))) | ('j', Some(&(_, 'k'))) | ('j', Some(&(_, 'K'))) => {
+            chars.next();
+            return Ok(Some((Card::JOKER, start)));
+        }
+        ('1', Some(&(_, '0'))) => {
+            chars.next();
+            Value::Ten
+        }
+        _ => Value::from_char(c).map_err(|e| e.offset(start))?,
+    };
+    let suit = match chars.next() {
+        None => return Err(CardStringConvertError::MissingSuit{position: start + 1}),
+        Some((position, c)) => Suit::from_char(c).map_err(|e| e.offset(position))?,
+    };
+    Ok(Some((Card::new(value, suit), start)))
+}
+
+/// parse a list of cards, e.g. "AsKd 7h", "Ah,Kh,10c", "A♠ K♥"
+///
+/// Cards can be separated by whitespace, commas or nothing, parse is case-insensitive.
+/// Duplicate cards are rejected, except jokers ("Jk" or "🃏"). Result can be `Vec<Card>` or `CardSet`.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet};
+///
+/// let cards: Vec<Card> = parse_cards("AsKd 7h").unwrap();
+/// assert_eq!(cards.len(), 3);
+/// let set: CardSet = parse_cards("ah,kh,10c").unwrap();
+/// assert!(set.contains("Th".parse().unwrap()) == false);
+/// assert!(parse_cards::<Vec<Card>>("AsAs").is_err());
+/// ```
+pub fn parse_cards<T: FromIterator<Card>>(s: &str) -> Result<T, CardStringConvertError> {
+    let mut chars = s.chars().enumerate().peekable();
+    let mut cards: Vec<Card> = Vec::new();
+    let mut seen: u64 = 0;
+    while let Some((card, position)) = parse_next_card(&mut chars)? {
+        let bit = 1 << card.card_index;
+        if seen & bit != 0 && !card.is_joker() {
+            return Err(CardStringConvertError::DuplicateCard{card, position});
+        }
+        seen |= bit;
+        cards.push(card);
+    }
+    Ok(cards.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_card(){
+        let ace_spade = Card::new(Value::Ace, Suit::Spade);
+        for s in ["As", "as", "AS", "A♠", " As "].iter() {
+            assert_eq!(s.parse::<Card>(), Ok(ace_spade));
+        }
+        assert_eq!("10h".parse::<Card>(), Ok(Card::new(Value::Ten, Suit::Heart)));
+        assert_eq!("10".parse::<Value>(), Ok(Value::Ten));
+        assert_eq!("Xs".parse::<Card>(), Err(CardStringConvertError::InvalidValue{ch: 'X', position: 0}));
+        assert_eq!("Ax".parse::<Card>(), Err(CardStringConvertError::InvalidSuit{ch: 'x', position: 1}));
+        assert_eq!("A".parse::<Card>(), Err(CardStringConvertError::MissingSuit{position: 1}));
+        assert_eq!("Asd".parse::<Card>(), Err(CardStringConvertError::UnexpectedChar{ch: 'd', position: 2}));
+        assert_eq!(ace_spade.to_string(), "As");
+    }
+
+    #[test]
+    fn test_parse_cards(){
+        let cards: Vec<Card> = parse_cards("AsKd 7h, 10c").unwrap();
+        assert_eq!(cards.iter().map(|c| c.to_string()).collect::<Vec<String>>(),
+                   vec!["As", "Kd", "7h", "Tc"]);
+        assert_eq!(parse_cards::<Vec<Card>>(""), Ok(vec![]));
+        assert_eq!(parse_cards::<Vec<Card>>("As Kd as"),
+                   Err(CardStringConvertError::DuplicateCard{card: Card::new(Value::Ace, Suit::Spade), position: 6}));
+        assert_eq!(parse_cards::<Vec<Card>>("As Kz"),
+                   Err(CardStringConvertError::InvalidSuit{ch: 'z', position: 4}));
+
+        let cards: Vec<Card> = parse_cards("Jk JdJK🃏").unwrap();
+        assert_eq!(cards, vec![Card::JOKER, Card::new(Value::Jack, Suit::Diamond), Card::JOKER, Card::JOKER]);
+        assert_eq!(Card::JOKER.to_string(), "Jk");
+        assert_eq!(Card::with_card_index(52, CardIndexPolicy::GroupBySuit), Card::JOKER);
     }
 }
@@ -0,0 +1,370 @@
+use crate::card::{parse_cards, Card, CardIndexPolicy, CardStringConvertError, Suit, Value};
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not, Sub, SubAssign};
+
+const FULL_MASK: u64 = (1 << 52) - 1;
+const JOKER_MASK: u64 = 1 << 52;
+
+// 每个花色在 GroupByValue 布局下的 mask: 每 4 bit 中的一位
+const SUIT_MASK: u64 = 0x1_1111_1111_1111;
+
+/// A set of cards backed by a u64 bitmask
+///
+/// bit n is the card which index is n with `CardIndexPolicy::GroupByValue`,
+/// so iteration is in card index order: 2s,2h,2c,2d,3s,...,Ad.
+/// Bit 52 is the joker, so a set holds at most one joker.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::{Card, Value, Suit};
+/// use pokerlib::CardSet;
+///
+/// let hole: CardSet = [Card::new(Value::Ace, Suit::Spade), Card::new(Value::Kine, Suit::Spade)][..].into();
+/// let deck = CardSet::full() - hole;
+/// assert_eq!(deck.len(), 50);
+/// assert!(deck.is_disjoint(hole));
+/// ```
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub struct CardSet {
+    mask: u64,
+}
+
+impl CardSet {
+    /// an empty set
+    pub const EMPTY: CardSet = CardSet{mask: 0};
+
+    /// an empty set
+    #[inline]
+    pub fn new() -> CardSet {
+        CardSet::EMPTY
+    }
+
+    /// all 52 cards
+    #[inline]
+    pub fn full() -> CardSet {
+        CardSet{mask: FULL_MASK}
+    }
+
+    /// the 36 cards of short-deck (6+), without 2-5
+    #[inline]
+    pub fn short_deck() -> CardSet {
+        CardSet{mask: FULL_MASK & !0xFFFF}
+    }
+
+    /// make set from raw mask, bits above 52 (the joker) are dropped
+    #[inline]
+    pub fn from_mask(mask: u64) -> CardSet {
+        CardSet{mask: mask & (FULL_MASK | JOKER_MASK)}
+    }
+
+    /// raw mask
+    #[inline]
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+
+    /// all cards of the suit
+    #[inline]
+    pub fn with_suit(suit: Suit) -> CardSet {
+        CardSet{mask: SUIT_MASK << suit.index()}
+    }
+
+    /// all cards of the value
+    #[inline]
+    pub fn with_value(value: Value) -> CardSet {
+        CardSet{mask: 0xF << (value.index() * 4)}
+    }
+
+    #[inline]
+    fn bit(card: Card) -> u64 {
+        1 << card.card_index(CardIndexPolicy::GroupByValue)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    #[inline]
+    pub fn contains(&self, card: Card) -> bool {
+        self.mask & CardSet::bit(card) != 0
+    }
+
+    /// add a card, return false if it is already in set
+    #[inline]
+    pub fn insert(&mut self, card: Card) -> bool {
+        let had = self.contains(card);
+        self.mask |= CardSet::bit(card);
+        !had
+    }
+
+    /// remove a card, return false if it is not in set
+    #[inline]
+    pub fn remove(&mut self, card: Card) -> bool {
+        let had = self.contains(card);
+        self.mask &= !CardSet::bit(card);
+        had
+    }
+
+    #[inline]
+    pub fn union(&self, other: CardSet) -> CardSet {
+        CardSet{mask: self.mask | other.mask}
+    }
+
+    #[inline]
+    pub fn intersection(&self, other: CardSet) -> CardSet {
+        CardSet{mask: self.mask & other.mask}
+    }
+
+    #[inline]
+    pub fn difference(&self, other: CardSet) -> CardSet {
+        CardSet{mask: self.mask & !other.mask}
+    }
+
+    #[inline]
+    pub fn is_disjoint(&self, other: CardSet) -> bool {
+        self.mask & other.mask == 0
+    }
+
+    #[inline]
+    pub fn is_subset(&self, other: CardSet) -> bool {
+        self.mask & !other.mask == 0
+    }
+
+    /// cards of the suit in this set
+    #[inline]
+    pub fn suit_cards(&self, suit: Suit) -> CardSet {
+        self.intersection(CardSet::with_suit(suit))
+    }
+
+    /// cards of the value in this set
+    #[inline]
+    pub fn value_cards(&self, value: Value) -> CardSet {
+        self.intersection(CardSet::with_value(value))
+    }
+
+    /// 13 bit mask of values of the suit in this set, bit 0 is Two, bit 12 is Ace
+    pub fn suit_value_mask(&self, suit: Suit) -> u16 {
+        let mut bits = (self.mask >> suit.index()) & SUIT_MASK;
+        let mut values = 0;
+        let mut i = 0;
+        while bits != 0 {
+            values |= ((bits & 1) as u16) << i;
+            bits >>= 4;
+            i += 1;
+        }
+        values
+    }
+
+    /// 13 bit mask of values in this set, bit 0 is Two, bit 12 is Ace
+    pub fn value_mask(&self) -> u16 {
+        (0..13).fold(0, |acc, i| if (self.mask >> (i*4)) & 0xF != 0 {acc | (1 << i)} else {acc})
+    }
+
+    /// iterate cards in index order
+    #[inline]
+    pub fn iter(&self) -> CardSetIter {
+        CardSetIter{mask: self.mask}
+    }
+
+    pub fn to_vec(&self) -> Vec<Card> {
+        self.iter().collect()
+    }
+}
+
+/// Iterator of `CardSet`, in card index order
+#[derive(Clone)]
+pub struct CardSetIter {
+    mask: u64,
+}
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    #[inline]
+    fn next(&mut self) -> Option<Card> {
+        if self.mask == 0 {
+            return None;
+        }
+        let index = self.mask.trailing_zeros() as u8;
+        self.mask &= self.mask - 1;
+        Some(Card::with_card_index(index, CardIndexPolicy::GroupByValue))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.mask.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(iter: I) -> CardSet {
+        iter.into_iter().cloned().collect()
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> CardSet {
+        cards.iter().collect()
+    }
+}
+
+impl From<Card> for CardSet {
+    fn from(card: Card) -> CardSet {
+        CardSet{mask: CardSet::bit(card)}
+    }
+}
+
+impl From<CardSet> for Vec<Card> {
+    fn from(set: CardSet) -> Vec<Card> {
+        set.to_vec()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+    fn bitor(self, rhs: CardSet) -> CardSet {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for CardSet {
+    fn bitor_assign(&mut self, rhs: CardSet) {
+        self.mask |= rhs.mask;
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+    fn bitand(self, rhs: CardSet) -> CardSet {
+        self.intersection(rhs)
+    }
+}
+
+impl BitAndAssign for CardSet {
+    fn bitand_assign(&mut self, rhs: CardSet) {
+        self.mask &= rhs.mask;
+    }
+}
+
+impl BitXor for CardSet {
+    type Output = CardSet;
+    fn bitxor(self, rhs: CardSet) -> CardSet {
+        CardSet{mask: self.mask ^ rhs.mask}
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+    fn sub(self, rhs: CardSet) -> CardSet {
+        self.difference(rhs)
+    }
+}
+
+impl SubAssign for CardSet {
+    fn sub_assign(&mut self, rhs: CardSet) {
+        self.mask &= !rhs.mask;
+    }
+}
+
+/// complement in 52 cards, the joker is never included
+impl Not for CardSet {
+    type Output = CardSet;
+    fn not(self) -> CardSet {
+        CardSet{mask: !self.mask & FULL_MASK}
+    }
+}
+
+/// parse by `card::parse_cards`, duplicate cards are rejected
+impl FromStr for CardSet {
+    type Err = CardStringConvertError;
+
+    fn from_str(s: &str) -> Result<CardSet, CardStringConvertError> {
+        parse_cards(s)
+    }
+}
+
+impl fmt::Display for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for card in self.iter() {
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_algebra(){
+        let cards = Card::one_desk_cards();
+        let a: CardSet = cards[0..10].iter().collect();
+        let b: CardSet = cards[5..20].iter().collect();
+        assert_eq!((a | b).len(), 20);
+        assert_eq!((a & b).len(), 5);
+        assert_eq!((a - b).to_vec(), cards[0..5].to_vec());
+        assert_eq!(!CardSet::EMPTY, CardSet::full());
+        assert_eq!(CardSet::full().iter().collect::<Vec<Card>>(), cards.to_vec());
+        assert!(a.contains(cards[9]) && !a.contains(cards[10]));
+    }
+
+    #[test]
+    fn test_suit_value_masks(){
+        assert_eq!(CardSet::with_suit(Suit::Club).len(), 13);
+        assert!(CardSet::with_suit(Suit::Club).iter().all(|c| c.suit() == Suit::Club));
+        assert!(CardSet::with_value(Value::Ten).iter().all(|c| c.value() == Value::Ten));
+        assert_eq!(CardSet::short_deck().len(), 36);
+        assert!(CardSet::short_deck().iter().all(|c| c.value() >= Value::Six));
+
+        let set: CardSet = [Card::new(Value::Ace, Suit::Heart), Card::new(Value::Two, Suit::Heart),
+                            Card::new(Value::Five, Suit::Club)].iter().collect();
+        assert_eq!(set.suit_value_mask(Suit::Heart), 0x1001);
+        assert_eq!(set.suit_value_mask(Suit::Club), 0x8);
+        assert_eq!(set.value_mask(), 0x1009);
+    }
+}
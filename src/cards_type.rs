@@ -1,4 +1,8 @@
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// Category of a poker hand, from worst to best
+///
+/// It's `non_exhaustive` since `Five` was added for wild cards, match it with a `_` arm.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum CardsType {
     High,
     Pair,
@@ -9,6 +13,8 @@ pub enum CardsType {
     Full,
     Four,
     StraightFlush,
+    /// five of a kind, only with wild cards
+    Five,
 }
 
 // 一些统计信息
@@ -25,3 +31,45 @@ pub const FOUR_RANK_COUNT: u16 = 156;
 pub const FLUSH_RANK_COUNT: u16 = 1277;
 pub const STRAIGHT_RANK_COUNT: u16 = 10;
 pub const STRAIGHT_FLUSH_RANK_COUNT: u16 = 10;
+pub const FIVE_RANK_COUNT: u16 = 13;
+
+/// all cards types without wild cards, from worst to best
+pub const ALL_CARDS_TYPES: [CardsType; 9] = [
+    CardsType::High, CardsType::Pair, CardsType::Pair2, CardsType::Three, CardsType::Straight,
+    CardsType::Flush, CardsType::Full, CardsType::Four, CardsType::StraightFlush];
+
+// 每个牌型的最小 rank, 按 CardsType 的顺序, Five 排在 7462 之后
+pub(crate) const FIRST_RANKS: [u16;10] = {
+    let counts = [HIGH_RANK_COUNT, PAIR_RANK_COUNT, PAIR2_RANK_COUNT, THREE_RANK_COUNT, STRAIGHT_RANK_COUNT,
+                  FLUSH_RANK_COUNT, FULL_RANK_COUNT, FOUR_RANK_COUNT, STRAIGHT_FLUSH_RANK_COUNT];
+    let mut ranks = [1;10];
+    let mut i = 1;
+    while i < 10 {
+        ranks[i] = ranks[i-1] + counts[i-1];
+        i += 1;
+    }
+    ranks
+};
+
+impl CardsType {
+    /// how many equivalence classes of this cards type
+    pub fn rank_count(&self) -> u16 {
+        match *self {
+            CardsType::High => HIGH_RANK_COUNT,
+            CardsType::Pair => PAIR_RANK_COUNT,
+            CardsType::Pair2 => PAIR2_RANK_COUNT,
+            CardsType::Three => THREE_RANK_COUNT,
+            CardsType::Straight => STRAIGHT_RANK_COUNT,
+            CardsType::Flush => FLUSH_RANK_COUNT,
+            CardsType::Full => FULL_RANK_COUNT,
+            CardsType::Four => FOUR_RANK_COUNT,
+            CardsType::StraightFlush => STRAIGHT_FLUSH_RANK_COUNT,
+            CardsType::Five => FIVE_RANK_COUNT,
+        }
+    }
+
+    /// the worst rank (1-based, in 1..=7462) of this cards type, `Five` is 7463
+    pub fn first_rank(&self) -> u16 {
+        FIRST_RANKS[*self as usize]
+    }
+}
@@ -0,0 +1,213 @@
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::rng::Rng;
+
+/// Hold'em betting round
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    /// board cards count at this street
+    pub fn board_len(&self) -> usize {
+        match *self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn => 4,
+            Street::River => 5,
+        }
+    }
+
+    /// new board cards dealt at this street
+    pub fn new_cards(&self) -> usize {
+        match *self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn | Street::River => 1,
+        }
+    }
+}
+
+/// A deck of cards
+///
+/// Cards are dealt from the top. A new deck is in card index order, call
+/// `shuffle` before dealing.
+///
+/// Example:
+/// ```
+/// use pokerlib::deck::{Deck, Street};
+/// use pokerlib::rng::SeededRng;
+///
+/// let mut deck = Deck::new();
+/// deck.shuffle(&mut SeededRng::new(42));
+/// let hole = deck.deal(2).unwrap();
+/// let flop = deck.deal_board(Street::Flop).unwrap();
+/// assert_eq!(flop.len(), 3);
+/// assert_eq!(deck.len(), 52 - 2 - 1 - 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Deck {
+    cards: Vec<Card>,
+    top: usize,
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
+
+impl Deck {
+    /// a full 52 cards deck
+    pub fn new() -> Deck {
+        Deck::with_cards(CardSet::full())
+    }
+
+    /// a 36 cards short-deck (6+) deck
+    pub fn short_deck() -> Deck {
+        Deck::with_cards(CardSet::short_deck())
+    }
+
+    /// a full 52 cards deck with `count` jokers added at the bottom
+    pub fn with_jokers(count: usize) -> Deck {
+        let mut deck = Deck::new();
+        deck.cards.extend(std::iter::repeat(Card::JOKER).take(count));
+        deck
+    }
+
+    /// a deck without the dead cards
+    pub fn without(dead: CardSet) -> Deck {
+        Deck::with_cards(!dead)
+    }
+
+    /// a deck of the cards
+    pub fn with_cards(cards: CardSet) -> Deck {
+        Deck{
+            cards: cards.to_vec(),
+            top: 0,
+        }
+    }
+
+    /// cards not dealt yet, top first
+    pub fn cards(&self) -> &[Card] {
+        &self.cards[self.top..]
+    }
+
+    /// cards not dealt yet
+    ///
+    /// A `CardSet` holds the joker once, use `jokers` for how many are left.
+    pub fn remaining(&self) -> CardSet {
+        self.cards().iter().collect()
+    }
+
+    /// jokers not dealt yet
+    pub fn jokers(&self) -> usize {
+        self.cards().iter().filter(|card| card.is_joker()).count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len() - self.top
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// shuffle the cards not dealt yet (Fisher-Yates)
+    pub fn shuffle<R: Rng>(&mut self, rng: &mut R) {
+        let cards = &mut self.cards[self.top..];
+        for i in (1..cards.len()).rev() {
+            let j = rng.gen_range(i as u64 + 1) as usize;
+            cards.swap(i, j);
+        }
+    }
+
+    /// deal one card from the top
+    pub fn deal_one(&mut self) -> Option<Card> {
+        let card = self.cards.get(self.top).cloned();
+        if card.is_some() {
+            self.top += 1;
+        }
+        card
+    }
+
+    /// deal n cards from the top, None if not enough cards
+    pub fn deal(&mut self, n: usize) -> Option<Vec<Card>> {
+        if self.len() < n {
+            return None;
+        }
+        let cards = self.cards[self.top..self.top+n].to_vec();
+        self.top += n;
+        Some(cards)
+    }
+
+    /// burn the top card
+    pub fn burn(&mut self) -> Option<Card> {
+        self.deal_one()
+    }
+
+    /// burn one card then deal the new board cards of the street
+    ///
+    /// Preflop deals nothing and burns nothing.
+    pub fn deal_board(&mut self, street: Street) -> Option<Vec<Card>> {
+        let count = street.new_cards();
+        if count == 0 {
+            return Some(vec![]);
+        }
+        if self.len() < count + 1 {
+            return None;
+        }
+        self.burn();
+        self.deal(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SeededRng;
+
+    #[test]
+    fn test_seeded_shuffle(){
+        let mut a = Deck::new();
+        let mut b = Deck::new();
+        a.shuffle(&mut SeededRng::new(7));
+        b.shuffle(&mut SeededRng::new(7));
+        assert_eq!(a.cards(), b.cards());
+        assert_ne!(a.cards(), Deck::new().cards());
+        assert_eq!(a.remaining(), CardSet::full());
+    }
+
+    #[test]
+    fn test_deal(){
+        let dead: CardSet = Card::one_desk_cards()[0..2].iter().collect();
+        let mut deck = Deck::without(dead);
+        assert_eq!(deck.len(), 50);
+        assert_eq!(deck.deal(2).unwrap(), Card::one_desk_cards()[2..4].to_vec());
+        assert_eq!(deck.deal_board(Street::Flop).unwrap(), Card::one_desk_cards()[5..8].to_vec());
+        assert_eq!(deck.deal_board(Street::Turn).unwrap().len(), 1);
+        assert_eq!(deck.deal(100), None);
+        assert_eq!(deck.len(), 42);
+    }
+
+    #[test]
+    fn test_jokers(){
+        let deck = Deck::with_jokers(1);
+        assert_eq!(deck.len(), 53);
+        assert_eq!(deck.remaining().len(), 53);
+        assert!(deck.remaining().contains(Card::JOKER));
+
+        let mut deck = Deck::with_jokers(2);
+        deck.shuffle(&mut SeededRng::new(3));
+        assert_eq!(deck.len(), 54);
+        assert_eq!(deck.jokers(), 2);
+        let cards = deck.deal(54).unwrap();
+        assert_eq!(cards.iter().filter(|card| card.is_joker()).count(), 2);
+        assert_eq!(deck.jokers(), 0);
+        assert_eq!(Deck::new().jokers(), 0);
+    }
+}
@@ -0,0 +1,877 @@
+use crate::Evaluator;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::range::Range;
+use crate::rng::{Rng, SeededRng};
+use crate::tools::combination::CombinationIter;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Error of equity calculation
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum EquityError {
+    /// need at least 2 players
+    TooFewPlayers(usize),
+    /// board should have 0..=5 cards
+    InvalidBoard(usize),
+    /// a card is used more than once in hands, board and dead cards
+    DuplicateCard(Card),
+    /// a joker, equity is only for the 52 cards deck
+    InvalidCard(Card),
+    /// not enough cards in deck to complete the board
+    NotEnoughCards,
+    /// Monte Carlo options have no stop condition
+    NoStopCondition,
+    /// the range (by index) has no combo left after removing board and dead cards
+    EmptyRange(usize),
+    /// every combination of the ranges' combos uses a card more than once
+    NoValidMatchup,
+}
+
+impl fmt::Display for EquityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquityError::TooFewPlayers(n) => write!(f, "need at least 2 players, got {}", n),
+            EquityError::InvalidBoard(n) => write!(f, "board should have 0 to 5 cards, got {}", n),
+            EquityError::DuplicateCard(card) => write!(f, "card {} is used more than once", card),
+            EquityError::InvalidCard(card) => write!(f, "card {} is not in the 52 cards deck", card),
+            EquityError::NotEnoughCards => write!(f, "not enough cards to complete the board"),
+            EquityError::NoStopCondition => write!(f, "no stop condition for Monte Carlo simulation"),
+            EquityError::EmptyRange(i) => write!(f, "range {} has no valid combo", i),
+            EquityError::NoValidMatchup => write!(f, "ranges have no matchup without card conflicts"),
+        }
+    }
+}
+
+impl Error for EquityError {}
+
+/// Showdown statistics of one player
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerEquity {
+    /// boards won alone
+    pub wins: u64,
+    /// boards split with other players
+    pub ties: u64,
+    /// boards lost
+    pub losses: u64,
+    /// sum of pot shares of split boards, e.g. a 3-way split adds 1/3
+    pub tie_share: f64,
+}
+
+impl PlayerEquity {
+    /// boards counted
+    pub fn total(&self) -> u64 {
+        self.wins + self.ties + self.losses
+    }
+
+    /// share of the pot: (wins + tie_share) / total
+    pub fn equity(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        (self.wins as f64 + self.tie_share) / self.total() as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.wins as f64 / self.total() as f64
+    }
+
+    pub fn tie_rate(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.ties as f64 / self.total() as f64
+    }
+}
+
+/// Equity of all players, in the order of input hands
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    pub players: Vec<PlayerEquity>,
+    /// boards counted
+    pub boards: u64,
+}
+
+impl EquityResult {
+    /// equity of every player
+    pub fn equities(&self) -> Vec<f64> {
+        self.players.iter().map(|p| p.equity()).collect()
+    }
+}
+
+// check jokers and duplicates of the cards and dead cards, return all used cards
+pub(crate) fn used_cards<'a>(cards: impl IntoIterator<Item = &'a Card>, dead: CardSet) -> Result<CardSet, EquityError> {
+    if dead.contains(Card::JOKER) {
+        return Err(EquityError::InvalidCard(Card::JOKER));
+    }
+    let mut used = dead;
+    for &card in cards {
+        if card.is_joker() {
+            return Err(EquityError::InvalidCard(card));
+        }
+        if !used.insert(card) {
+            return Err(EquityError::DuplicateCard(card));
+        }
+    }
+    Ok(used)
+}
+
+// check hands, board and dead cards, return all used cards
+pub(crate) fn check_cards(hands: &[[Card;2]], board: &[Card], dead: CardSet) -> Result<CardSet, EquityError> {
+    if hands.len() < 2 {
+        return Err(EquityError::TooFewPlayers(hands.len()));
+    }
+    if board.len() > 5 {
+        return Err(EquityError::InvalidBoard(board.len()));
+    }
+    let used = used_cards(hands.iter().flat_map(|h| h.iter()).chain(board.iter()), dead)?;
+    if 52 - used.len() < 5 - board.len() {
+        return Err(EquityError::NotEnoughCards);
+    }
+    Ok(used)
+}
+
+// Every player's 7 cards, the board part is filled per showdown
+pub(crate) struct Showdown<T> {
+    cards: Vec<[T;7]>,
+    values: Vec<u32>,
+    best: u32,
+    winners: usize,
+}
+
+impl<T: Copy> Showdown<T> {
+    pub(crate) fn new<E: Evaluator<CardType=T>>(evaluator: &E, hands: &[[Card;2]], board: &[Card]) -> Showdown<T> {
+        let blank = evaluator.make_card(&hands[0][0]);
+        let cards = hands.iter().map(|hand| {
+            let mut cards = [blank;7];
+            cards[0] = evaluator.make_card(&hand[0]);
+            cards[1] = evaluator.make_card(&hand[1]);
+            for (i, card) in board.iter().enumerate() {
+                cards[2+i] = evaluator.make_card(card);
+            }
+            cards
+        }).collect();
+        Showdown{
+            cards,
+            values: vec![0; hands.len()],
+            best: 0,
+            winners: 0,
+        }
+    }
+
+    pub(crate) fn set_card(&mut self, board_index: usize, card: T) {
+        for cards in self.cards.iter_mut() {
+            cards[2+board_index] = card;
+        }
+    }
+
+    /// eval all players, add result to stats
+    pub(crate) fn eval<E: Evaluator<CardType=T>>(&mut self, evaluator: &E, players: &mut [PlayerEquity]) {
+        self.best = 0;
+        for (value, cards) in self.values.iter_mut().zip(self.cards.iter()) {
+            *value = evaluator.eval(cards);
+            if *value > self.best {
+                self.best = *value;
+            }
+        }
+        let best = self.best;
+        self.winners = self.values.iter().filter(|&&v| v == best).count();
+        for (player, &value) in players.iter_mut().zip(self.values.iter()) {
+            if value != best {
+                player.losses += 1;
+            }else if self.winners == 1 {
+                player.wins += 1;
+            }else{
+                player.ties += 1;
+                player.tie_share += 1.0 / self.winners as f64;
+            }
+        }
+    }
+
+    /// pot share of a player in last showdown
+    pub(crate) fn payoff(&self, player: usize) -> f64 {
+        if self.values[player] == self.best {
+            1.0 / self.winners as f64
+        }else{
+            0.0
+        }
+    }
+}
+
+/// Exact equity by enumerating every remaining board
+///
+/// - hands: every player's hole cards
+/// - board: known board cards, 0 to 5
+/// - dead: cards known not in deck
+///
+/// Heads-up preflop is 1712304 boards, use Monte Carlo for big cases.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, NativeEvaluator};
+/// use pokerlib::equity::exact_equity;
+///
+/// let aa: Vec<Card> = parse_cards("AsAh").unwrap();
+/// let kk: Vec<Card> = parse_cards("KdKc").unwrap();
+/// let hands = [[aa[0], aa[1]], [kk[0], kk[1]]];
+/// let board: Vec<Card> = parse_cards("2s7hQd").unwrap();
+/// let result = exact_equity(&NativeEvaluator::new(), &hands, &board, CardSet::new()).unwrap();
+/// assert_eq!(result.boards, 990);
+/// assert!(result.players[0].equity() > 0.9);
+/// ```
+pub fn exact_equity<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hands: &[[Card;2]],
+    board: &[Card],
+    dead: CardSet,
+) -> Result<EquityResult, EquityError> {
+    let used = check_cards(hands, board, dead)?;
+    let deck: Vec<T> = (!used).iter().map(|card| evaluator.make_card(&card)).collect();
+    let mut showdown = Showdown::new(evaluator, hands, board);
+    let mut players = vec![PlayerEquity::default(); hands.len()];
+
+    let missing = 5 - board.len();
+    let mut boards = 0;
+    if missing == 0 {
+        showdown.eval(evaluator, &mut players);
+        boards += 1;
+    }else{
+        let mut it = CombinationIter::new(deck.len(), missing);
+        let mut indexes = [0;5];
+        while it.move_next(&mut indexes) {
+            for (i, &index) in indexes[0..missing].iter().enumerate() {
+                showdown.set_card(board.len() + i, deck[index]);
+            }
+            showdown.eval(evaluator, &mut players);
+            boards += 1;
+        }
+    }
+
+    Ok(EquityResult{players, boards})
+}
+
+/// Stop conditions of Monte Carlo simulation
+///
+/// Simulation stops when any condition is reached. The result is reproducible
+/// from the seed, unless it is stopped by `max_duration`.
+#[derive(Debug, Clone)]
+pub struct MonteCarloOptions {
+    /// rng seed
+    pub seed: u64,
+    /// max boards to sample
+    pub max_iterations: Option<u64>,
+    /// max running time
+    pub max_duration: Option<Duration>,
+    /// stop when every player's standard error is not greater than it
+    pub target_std_error: Option<f64>,
+    /// check duration and precision every so many boards
+    pub check_interval: u64,
+}
+
+impl Default for MonteCarloOptions {
+    fn default() -> Self {
+        MonteCarloOptions{
+            seed: 0,
+            max_iterations: Some(100_000),
+            max_duration: None,
+            target_std_error: None,
+            check_interval: 1000,
+        }
+    }
+}
+
+/// Monte Carlo equity estimate
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityEstimate {
+    /// sampled showdown statistics
+    pub result: EquityResult,
+    /// standard error of every player's equity
+    pub std_errors: Vec<f64>,
+}
+
+impl EquityEstimate {
+    /// estimated equity of every player
+    pub fn equities(&self) -> Vec<f64> {
+        self.result.equities()
+    }
+
+    /// 95% confidence interval of a player's equity
+    pub fn confidence_interval(&self, player: usize) -> (f64, f64) {
+        let equity = self.result.players[player].equity();
+        let margin = 1.96 * self.std_errors[player];
+        ((equity - margin).max(0.0), (equity + margin).min(1.0))
+    }
+}
+
+// standard errors from sums of squared payoffs
+pub(crate) fn std_errors(players: &[PlayerEquity], square_sums: &[f64]) -> Vec<f64> {
+    players.iter().zip(square_sums.iter()).map(|(player, &square_sum)| {
+        let n = player.total() as f64;
+        if n < 2.0 {
+            return 1.0;
+        }
+        let mean = player.equity();
+        let variance = (square_sum / n - mean * mean).max(0.0) * n / (n - 1.0);
+        (variance / n).sqrt()
+    }).collect()
+}
+
+// check stop conditions, called every check_interval iterations
+pub(crate) fn should_stop(options: &MonteCarloOptions, start: Instant, iterations: u64, std_errors: impl FnOnce() -> Vec<f64>) -> bool {
+    if let Some(max_duration) = options.max_duration {
+        if start.elapsed() >= max_duration {
+            return true;
+        }
+    }
+    if let Some(target) = options.target_std_error {
+        if std_errors().iter().all(|&e| e <= target) {
+            return true;
+        }
+    }
+    options.max_iterations.is_some_and(|max| iterations >= max)
+}
+
+/// Estimate equity by sampling random boards
+///
+/// Arguments are same as `exact_equity`, see `MonteCarloOptions` for stop conditions.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, NativeEvaluator};
+/// use pokerlib::equity::{monte_carlo_equity, MonteCarloOptions};
+///
+/// let aa: Vec<Card> = parse_cards("AsAh").unwrap();
+/// let kk: Vec<Card> = parse_cards("KdKc").unwrap();
+/// let options = MonteCarloOptions{seed: 7, max_iterations: Some(20000), ..Default::default()};
+/// let estimate = monte_carlo_equity(&NativeEvaluator::new(), &[[aa[0], aa[1]], [kk[0], kk[1]]],
+///                                   &[], CardSet::new(), &options).unwrap();
+/// let (low, high) = estimate.confidence_interval(0);
+/// assert!(low < estimate.equities()[0] && estimate.equities()[0] < high);
+/// assert!((estimate.equities()[0] - 0.81).abs() < 0.02);
+/// ```
+pub fn monte_carlo_equity<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hands: &[[Card;2]],
+    board: &[Card],
+    dead: CardSet,
+    options: &MonteCarloOptions,
+) -> Result<EquityEstimate, EquityError> {
+    if options.max_iterations.is_none() && options.max_duration.is_none() && options.target_std_error.is_none() {
+        return Err(EquityError::NoStopCondition);
+    }
+    let used = check_cards(hands, board, dead)?;
+    let mut deck: Vec<T> = (!used).iter().map(|card| evaluator.make_card(&card)).collect();
+    let mut showdown = Showdown::new(evaluator, hands, board);
+    let mut players = vec![PlayerEquity::default(); hands.len()];
+    let mut square_sums = vec![0.0; hands.len()];
+    let mut rng = SeededRng::new(options.seed);
+    let start = Instant::now();
+    let check_interval = options.check_interval.max(1);
+
+    let missing = 5 - board.len();
+    let mut iterations = 0;
+    loop {
+        // partial Fisher-Yates: the first `missing` cards are the sampled board
+        for i in 0..missing {
+            let j = i + rng.gen_range((deck.len() - i) as u64) as usize;
+            deck.swap(i, j);
+            showdown.set_card(board.len() + i, deck[i]);
+        }
+        showdown.eval(evaluator, &mut players);
+        for (i, square_sum) in square_sums.iter_mut().enumerate() {
+            let payoff = showdown.payoff(i);
+            *square_sum += payoff * payoff;
+        }
+        iterations += 1;
+
+        if (iterations % check_interval == 0 || options.max_iterations == Some(iterations))
+            && should_stop(options, start, iterations, || std_errors(&players, &square_sums)) {
+            break;
+        }
+    }
+
+    let std_errors = std_errors(&players, &square_sums);
+    Ok(EquityEstimate{
+        result: EquityResult{players, boards: iterations},
+        std_errors,
+    })
+}
+
+/// Equity of one combo in the first range of range-vs-range equity
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComboEquity {
+    pub combo: [Card;2],
+    /// share of this combo in all counted matchups, weights and card removal considered
+    pub frequency: f64,
+    /// equity of this combo against the other ranges
+    pub equity: f64,
+}
+
+/// Range-vs-range equity
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEquityResult {
+    /// equity of every range
+    pub equities: Vec<f64>,
+    /// equity of every combo of the first range, combos never counted are not included
+    pub combos: Vec<ComboEquity>,
+    /// (matchup, board) pairs counted
+    pub showdowns: u64,
+}
+
+/// Monte Carlo range-vs-range equity estimate
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeEquityEstimate {
+    pub result: RangeEquityResult,
+    /// standard error of every range's equity
+    pub std_errors: Vec<f64>,
+}
+
+impl RangeEquityEstimate {
+    /// 95% confidence interval of a range's equity
+    pub fn confidence_interval(&self, range: usize) -> (f64, f64) {
+        let equity = self.result.equities[range];
+        let margin = 1.96 * self.std_errors[range];
+        ((equity - margin).max(0.0), (equity + margin).min(1.0))
+    }
+}
+
+// combos of a range which don't conflict with board and dead cards
+struct RangeCombos<T> {
+    combos: Vec<[Card;2]>,
+    masks: Vec<CardSet>,
+    weights: Vec<f64>,
+    cards: Vec<[T;7]>,
+    values: Vec<u32>,
+}
+
+impl<T: Copy> RangeCombos<T> {
+    fn new<E: Evaluator<CardType=T>>(evaluator: &E, range: &Range, board: &[Card], dead: CardSet) -> RangeCombos<T> {
+        let used = dead | board.iter().collect();
+        let mut range_combos = RangeCombos{
+            combos: vec![], masks: vec![], weights: vec![], cards: vec![], values: vec![],
+        };
+        for (combo, weight) in range.combos_without(used) {
+            let mut cards = [evaluator.make_card(&combo[0]);7];
+            cards[1] = evaluator.make_card(&combo[1]);
+            for (i, card) in board.iter().enumerate() {
+                cards[2+i] = evaluator.make_card(card);
+            }
+            range_combos.combos.push(combo);
+            range_combos.masks.push(combo[..].into());
+            range_combos.weights.push(weight as f64);
+            range_combos.cards.push(cards);
+            range_combos.values.push(0);
+        }
+        range_combos
+    }
+}
+
+fn check_ranges<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    ranges: &[Range],
+    board: &[Card],
+    dead: CardSet,
+) -> Result<Vec<RangeCombos<T>>, EquityError> {
+    if ranges.len() < 2 {
+        return Err(EquityError::TooFewPlayers(ranges.len()));
+    }
+    if board.len() > 5 {
+        return Err(EquityError::InvalidBoard(board.len()));
+    }
+    let used = used_cards(board, dead)?;
+    if 52 - used.len() < 5 - board.len() + 2 * ranges.len() {
+        return Err(EquityError::NotEnoughCards);
+    }
+    let all: Vec<RangeCombos<T>> = ranges.iter().map(|range| RangeCombos::new(evaluator, range, board, dead)).collect();
+    if let Some(i) = all.iter().position(|r| r.combos.is_empty()) {
+        return Err(EquityError::EmptyRange(i));
+    }
+    // 组合少的范围先选, 冲突的分支更早剪掉
+    let mut order: Vec<&RangeCombos<T>> = all.iter().collect();
+    order.sort_by_key(|r| r.combos.len());
+    if !has_matchup(&order, CardSet::new()) {
+        return Err(EquityError::NoValidMatchup);
+    }
+    Ok(all)
+}
+
+// 回溯搜索: 每个范围选一个组合, 互相不冲突
+fn has_matchup<T>(ranges: &[&RangeCombos<T>], used: CardSet) -> bool {
+    match ranges.split_first() {
+        None => true,
+        Some((range, rest)) => range.masks.iter()
+            .any(|&mask| mask.is_disjoint(used) && has_matchup(rest, used | mask)),
+    }
+}
+
+// weighted sums of exact range-vs-range enumeration
+struct RangeAccumulator {
+    weight: f64,
+    shares: Vec<f64>,
+    combo_weights: Vec<f64>,
+    combo_shares: Vec<f64>,
+    showdowns: u64,
+}
+
+impl RangeAccumulator {
+    // enumerate matchups of combos not using `used`, range by range
+    fn add<T>(&mut self, ranges: &[RangeCombos<T>], level: usize, used: CardSet, weight: f64, picked: &mut Vec<usize>) {
+        if level == ranges.len() {
+            let values: Vec<u32> = picked.iter().enumerate().map(|(r, &c)| ranges[r].values[c]).collect();
+            let best = *values.iter().max().unwrap();
+            let winners = values.iter().filter(|&&v| v == best).count() as f64;
+            for (r, &value) in values.iter().enumerate() {
+                if value == best {
+                    self.shares[r] += weight / winners;
+                }
+            }
+            self.weight += weight;
+            self.combo_weights[picked[0]] += weight;
+            if values[0] == best {
+                self.combo_shares[picked[0]] += weight / winners;
+            }
+            self.showdowns += 1;
+            return;
+        }
+        let range = &ranges[level];
+        for c in 0..range.combos.len() {
+            if !range.masks[c].is_disjoint(used) {
+                continue;
+            }
+            picked.push(c);
+            self.add(ranges, level + 1, used | range.masks[c], weight * range.weights[c], picked);
+            picked.pop();
+        }
+    }
+}
+
+fn combo_equities<T>(hero: &RangeCombos<T>, weights: &[f64], shares: &[f64]) -> Vec<ComboEquity> {
+    let total: f64 = weights.iter().sum();
+    hero.combos.iter().enumerate()
+        .filter(|&(i, _)| weights[i] > 0.0)
+        .map(|(i, &combo)| ComboEquity{
+            combo,
+            frequency: weights[i] / total,
+            equity: shares[i] / weights[i],
+        })
+        .collect()
+}
+
+/// Exact range-vs-range equity by enumerating every matchup and every remaining board
+///
+/// Combos using board or dead cards are removed, matchups sharing a card are skipped
+/// (blocker effects), every matchup is weighted by the product of combo weights.
+/// The cost is `boards * combos of all ranges`, use `monte_carlo_range_equity`
+/// for preflop or wide multiway ranges.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, NativeEvaluator, Range};
+/// use pokerlib::equity::exact_range_equity;
+///
+/// let ranges: Vec<Range> = vec!["AA,KK".parse().unwrap(), "AKs".parse().unwrap()];
+/// let board: Vec<Card> = parse_cards("2s7hQd9c").unwrap();
+/// let result = exact_range_equity(&NativeEvaluator::new(), &ranges, &board, CardSet::new()).unwrap();
+/// assert!(result.equities[0] > 0.8);
+/// ```
+pub fn exact_range_equity<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    ranges: &[Range],
+    board: &[Card],
+    dead: CardSet,
+) -> Result<RangeEquityResult, EquityError> {
+    let mut all = check_ranges(evaluator, ranges, board, dead)?;
+    let used = dead | board.iter().collect();
+    let deck: Vec<Card> = (!used).to_vec();
+    let deck_cards: Vec<T> = deck.iter().map(|card| evaluator.make_card(card)).collect();
+
+    let mut acc = RangeAccumulator{
+        weight: 0.0,
+        shares: vec![0.0; ranges.len()],
+        combo_weights: vec![0.0; all[0].combos.len()],
+        combo_shares: vec![0.0; all[0].combos.len()],
+        showdowns: 0,
+    };
+    let missing = 5 - board.len();
+    let mut it = CombinationIter::new(deck.len(), missing);
+    let mut indexes = [0;5];
+    let mut picked = Vec::with_capacity(ranges.len());
+    // no missing card: one empty runout
+    let mut first = true;
+    while (missing == 0 && first) || (missing > 0 && it.move_next(&mut indexes)) {
+        first = false;
+        let runout: CardSet = indexes[0..missing].iter().map(|&i| deck[i]).collect();
+        for range in all.iter_mut() {
+            for c in 0..range.combos.len() {
+                if !range.masks[c].is_disjoint(runout) {
+                    continue;
+                }
+                for (i, &index) in indexes[0..missing].iter().enumerate() {
+                    range.cards[c][2 + board.len() + i] = deck_cards[index];
+                }
+                range.values[c] = evaluator.eval(&range.cards[c]);
+            }
+        }
+        acc.add(&all, 0, runout, 1.0, &mut picked);
+    }
+
+    if acc.weight == 0.0 {
+        return Err(EquityError::NoValidMatchup);
+    }
+    Ok(RangeEquityResult{
+        equities: acc.shares.iter().map(|&share| share / acc.weight).collect(),
+        combos: combo_equities(&all[0], &acc.combo_weights, &acc.combo_shares),
+        showdowns: acc.showdowns,
+    })
+}
+
+// pick a combo index by weight, cumulative weights are ascending
+fn sample_weighted<R: Rng>(rng: &mut R, cumulative: &[f64]) -> usize {
+    let x = rng.gen_f64() * cumulative[cumulative.len() - 1];
+    cumulative.partition_point(|&c| c <= x).min(cumulative.len() - 1)
+}
+
+/// Estimate range-vs-range equity by sampling
+///
+/// Every sample picks one combo from each range by weight, rejects matchups sharing
+/// a card, then picks a random board. Stop conditions and reproducibility are same
+/// as `monte_carlo_equity`. `NoValidMatchup` is checked before sampling, when
+/// `max_duration` is reached before any valid sample, `showdowns` is 0.
+///
+/// Example:
+/// ```
+/// use pokerlib::{CardSet, NativeEvaluator, Range};
+/// use pokerlib::equity::{monte_carlo_range_equity, MonteCarloOptions};
+///
+/// let ranges: Vec<Range> = vec!["QQ+,AKs".parse().unwrap(), "22-TT".parse().unwrap()];
+/// let options = MonteCarloOptions{seed: 7, max_iterations: Some(20000), ..Default::default()};
+/// let estimate = monte_carlo_range_equity(&NativeEvaluator::new(), &ranges, &[], CardSet::new(), &options).unwrap();
+/// assert_eq!(estimate.result.showdowns, 20000);
+/// assert!(estimate.result.equities[0] > 0.6);
+/// ```
+pub fn monte_carlo_range_equity<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    ranges: &[Range],
+    board: &[Card],
+    dead: CardSet,
+    options: &MonteCarloOptions,
+) -> Result<RangeEquityEstimate, EquityError> {
+    if options.max_iterations.is_none() && options.max_duration.is_none() && options.target_std_error.is_none() {
+        return Err(EquityError::NoStopCondition);
+    }
+    let mut all = check_ranges(evaluator, ranges, board, dead)?;
+    let used = dead | board.iter().collect();
+    let cumulatives: Vec<Vec<f64>> = all.iter().map(|range| {
+        range.weights.iter().scan(0.0, |sum, &w| {*sum += w; Some(*sum)}).collect()
+    }).collect();
+
+    let mut rng = SeededRng::new(options.seed);
+    let start = Instant::now();
+    let check_interval = options.check_interval.max(1);
+    let mut players = vec![PlayerEquity::default(); ranges.len()];
+    let mut square_sums = vec![0.0; ranges.len()];
+    let mut combo_counts = vec![0.0; all[0].combos.len()];
+    let mut combo_shares = vec![0.0; all[0].combos.len()];
+    let mut picked = vec![0; ranges.len()];
+    let mut values = vec![0; ranges.len()];
+    let missing = 5 - board.len();
+    let mut iterations = 0;
+    let mut rejections: u64 = 0;
+    let mut deck = Vec::with_capacity(52);
+    loop {
+        // pick matchup
+        let mut matchup_used = used;
+        let mut ok = true;
+        for (r, cumulative) in cumulatives.iter().enumerate() {
+            let c = sample_weighted(&mut rng, cumulative);
+            if !all[r].masks[c].is_disjoint(matchup_used) {
+                ok = false;
+                break;
+            }
+            matchup_used |= all[r].masks[c];
+            picked[r] = c;
+        }
+        if !ok {
+            rejections += 1;
+            // 冲突很多时也要按时停下
+            if rejections % check_interval == 0 && options.max_duration.is_some_and(|d| start.elapsed() >= d) {
+                break;
+            }
+            continue;
+        }
+
+        // pick board
+        deck.clear();
+        deck.extend((!matchup_used).iter());
+        for i in 0..missing {
+            let j = i + rng.gen_range((deck.len() - i) as u64) as usize;
+            deck.swap(i, j);
+        }
+        for (r, range) in all.iter_mut().enumerate() {
+            let cards = &mut range.cards[picked[r]];
+            for (i, card) in deck[0..missing].iter().enumerate() {
+                cards[2 + board.len() + i] = evaluator.make_card(card);
+            }
+            values[r] = evaluator.eval(cards);
+        }
+
+        let best = *values.iter().max().unwrap();
+        let winners = values.iter().filter(|&&v| v == best).count();
+        for (r, player) in players.iter_mut().enumerate() {
+            let payoff = if values[r] != best {
+                player.losses += 1;
+                0.0
+            }else if winners == 1 {
+                player.wins += 1;
+                1.0
+            }else{
+                player.ties += 1;
+                player.tie_share += 1.0 / winners as f64;
+                1.0 / winners as f64
+            };
+            square_sums[r] += payoff * payoff;
+            if r == 0 {
+                combo_counts[picked[0]] += 1.0;
+                combo_shares[picked[0]] += payoff;
+            }
+        }
+        iterations += 1;
+
+        if (iterations % check_interval == 0 || options.max_iterations == Some(iterations))
+            && should_stop(options, start, iterations, || std_errors(&players, &square_sums)) {
+            break;
+        }
+    }
+
+    let std_errors = std_errors(&players, &square_sums);
+    Ok(RangeEquityEstimate{
+        result: RangeEquityResult{
+            equities: players.iter().map(|p| p.equity()).collect(),
+            combos: combo_equities(&all[0], &combo_counts, &combo_shares),
+            showdowns: iterations,
+        },
+        std_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::evaluator::NativeEvaluator;
+    use crate::test_util::hand;
+
+    #[test]
+    fn test_exact_equity(){
+        let evaluator = NativeEvaluator::new();
+        let board: Vec<Card> = parse_cards("AhKd2c9s").unwrap();
+        let result = exact_equity(&evaluator, &[hand("AsAd"), hand("QhJh"), hand("7c7d")],
+                                  &board, CardSet::new()).unwrap();
+        assert_eq!(result.boards, 42);
+        // QJ needs a T: 4 outs, all of them make a straight
+        assert_eq!(result.players[1].wins, 4);
+        assert_eq!(result.players[0].wins, 38);
+        assert!((result.equities().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // board plays
+        let board: Vec<Card> = parse_cards("AhKdQcJsTs").unwrap();
+        let result = exact_equity(&evaluator, &[hand("2s3d"), hand("4c5c")],
+                                  &board, CardSet::new()).unwrap();
+        assert_eq!(result.players[0].ties, 1);
+        assert_eq!(result.players[0].equity(), 0.5);
+    }
+
+    #[test]
+    fn test_monte_carlo_equity(){
+        let evaluator = NativeEvaluator::new();
+        let hands = [hand("AsAd"), hand("QhJh"), hand("7c7d")];
+        let board: Vec<Card> = parse_cards("Ah2c9s").unwrap();
+        let exact = exact_equity(&evaluator, &hands, &board, CardSet::new()).unwrap();
+
+        let options = MonteCarloOptions{seed: 1, max_iterations: None, target_std_error: Some(0.001),
+                                        ..Default::default()};
+        let estimate = monte_carlo_equity(&evaluator, &hands, &board, CardSet::new(), &options).unwrap();
+        for (player, equity) in exact.equities().iter().enumerate() {
+            let (low, high) = estimate.confidence_interval(player);
+            assert!(low <= *equity && *equity <= high);
+            assert!(estimate.std_errors[player] <= 0.001);
+        }
+        // reproducible
+        assert_eq!(monte_carlo_equity(&evaluator, &hands, &board, CardSet::new(), &options).unwrap(), estimate);
+
+        let options = MonteCarloOptions{max_iterations: Some(1234), ..Default::default()};
+        let estimate = monte_carlo_equity(&evaluator, &hands, &board, CardSet::new(), &options).unwrap();
+        assert_eq!(estimate.result.boards, 1234);
+    }
+
+    #[test]
+    fn test_range_equity(){
+        let evaluator = NativeEvaluator::new();
+        let board: Vec<Card> = parse_cards("2s7hQd9c").unwrap();
+
+        // single combo ranges equal to hand equity
+        let ranges: Vec<Range> = vec!["AsAd".parse().unwrap(), "QhJh".parse().unwrap()];
+        let result = exact_range_equity(&evaluator, &ranges, &board, CardSet::new()).unwrap();
+        let exact = exact_equity(&evaluator, &[hand("AsAd"), hand("QhJh")], &board, CardSet::new()).unwrap();
+        assert_eq!(result.equities, exact.equities());
+
+        // blockers: AA vs AKs, the As blocks AsKs
+        let ranges: Vec<Range> = vec!["AsAh".parse().unwrap(), "AKs".parse().unwrap()];
+        let result = exact_range_equity(&evaluator, &ranges, &board, CardSet::new()).unwrap();
+        assert_eq!(result.showdowns, 2 * 44);
+        assert_eq!(result.combos.len(), 1);
+        assert_eq!(result.combos[0].frequency, 1.0);
+
+        // weights and per-combo breakdown
+        let ranges: Vec<Range> = vec!["AA, 33@50%".parse().unwrap(), "QQ, JJ".parse().unwrap()];
+        let result = exact_range_equity(&evaluator, &ranges, &board, CardSet::new()).unwrap();
+        let aa_frequency: f64 = result.combos.iter()
+            .filter(|c| c.combo[0].value() == crate::card::Value::Ace).map(|c| c.frequency).sum();
+        assert!((aa_frequency - 2.0 / 3.0).abs() < 1e-9);
+        assert!(result.combos.iter().all(|c| (c.combo[0].value() == crate::card::Value::Ace) == (c.equity > 0.5)));
+        assert!((result.equities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        let options = MonteCarloOptions{seed: 3, max_iterations: Some(50000), ..Default::default()};
+        let estimate = monte_carlo_range_equity(&evaluator, &ranges, &board, CardSet::new(), &options).unwrap();
+        let (low, high) = estimate.confidence_interval(0);
+        assert!(low <= result.equities[0] && result.equities[0] <= high);
+
+        let ranges: Vec<Range> = vec!["AsAh".parse().unwrap(), "AsAh".parse().unwrap()];
+        assert_eq!(exact_range_equity(&evaluator, &ranges, &board, CardSet::new()), Err(EquityError::NoValidMatchup));
+        assert_eq!(monte_carlo_range_equity(&evaluator, &ranges, &board, CardSet::new(), &options).map(|_| ()),
+                   Err(EquityError::NoValidMatchup));
+        // 多人: 每两个范围之间都有不冲突的组合, 三个一起没有
+        let ranges: Vec<Range> = vec!["AA".parse().unwrap(), "AA".parse().unwrap(), "AA".parse().unwrap()];
+        assert_eq!(monte_carlo_range_equity(&evaluator, &ranges, &board, CardSet::new(), &options).map(|_| ()),
+                   Err(EquityError::NoValidMatchup));
+    }
+
+    #[test]
+    fn test_check_cards(){
+        let evaluator = NativeEvaluator::new();
+        assert_eq!(exact_equity(&evaluator, &[hand("AsAd")], &[], CardSet::new()),
+                   Err(EquityError::TooFewPlayers(1)));
+        assert_eq!(exact_equity(&evaluator, &[hand("AsAd"), hand("AsKd")], &[], CardSet::new()),
+                   Err(EquityError::DuplicateCard("As".parse().unwrap())));
+
+        // 王牌不在 52 张牌里
+        let options = MonteCarloOptions{max_iterations: Some(10), ..Default::default()};
+        assert_eq!(exact_equity(&evaluator, &[hand("JkAs"), hand("KdKh")], &[], CardSet::new()),
+                   Err(EquityError::InvalidCard(Card::JOKER)));
+        assert_eq!(monte_carlo_equity(&evaluator, &[hand("AsAd"), hand("KdKh")], &[], Card::JOKER.into(), &options).map(|_| ()),
+                   Err(EquityError::InvalidCard(Card::JOKER)));
+        let ranges: Vec<Range> = vec!["AA".parse().unwrap(), "KK".parse().unwrap()];
+        let board: Vec<Card> = parse_cards("2s7hJk").unwrap();
+        assert_eq!(exact_range_equity(&evaluator, &ranges, &board, CardSet::new()).map(|_| ()),
+                   Err(EquityError::InvalidCard(Card::JOKER)));
+        assert_eq!(monte_carlo_range_equity(&evaluator, &ranges, &board, CardSet::new(), &options).map(|_| ()),
+                   Err(EquityError::InvalidCard(Card::JOKER)));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::card::{Card, Value};
+use crate::cards_type::CardsType;
+use crate::LowEvaluator;
+use crate::tools::combination::CombinationIter;
+
+/// A-5 low rank, the higher the better
+///
+/// Aces are low, straights and flushes don't count, so 5-4-3-2-A (the wheel)
+/// is the best hand. Hands with pairs are worse than any no-pair hand.
+///
+/// format: (5 - category) << 20 | (0xFFFFF - 5 value nibbles), nibbles are ordered as
+/// `values` returns, Ace=1 ... Kine=13.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct LowRank(u32);
+
+// make_card 给 joker 的值, eval_low 遇到它返回 None
+const JOKER_INDEX: u8 = 13;
+
+// 0=A, 1=2, ..., 12=K
+#[inline]
+fn low_index(value: Value) -> u8 {
+    (value.index() + 1) % 13
+}
+
+#[inline]
+fn low_value(index: u8) -> Value {
+    Value::from_index((index + 12) % 13)
+}
+
+const CATEGORIES: [CardsType;6] = [
+    CardsType::High, CardsType::Pair, CardsType::Pair2, CardsType::Three, CardsType::Full, CardsType::Four];
+
+impl LowRank {
+    /// the best A-5 low: 5-4-3-2-A
+    pub const BEST: LowRank = LowRank((5 << 20) | (0xFFFFF - 0x54321));
+
+    // 5 张牌的 ace-low 牌值 (0-12)
+    fn with_low_indexes(indexes: &[u8]) -> LowRank {
+        let mut counts = [0u8;13];
+        for &i in indexes.iter() {
+            counts[i as usize] += 1;
+        }
+        // 按 (张数, 牌值) 从大到小排列
+        let mut groups: Vec<(u8, u8)> = (0..13u8).rev()
+            .filter(|&i| counts[i as usize] > 0)
+            .map(|i| (counts[i as usize], i))
+            .collect();
+        groups.sort_by_key(|&(count, _)| std::cmp::Reverse(count));
+        let category = match (groups[0].0, groups.len()) {
+            (1, _) => 0,
+            (2, 4) => 1,
+            (2, _) => 2,
+            (3, 3) => 3,
+            (3, _) => 4,
+            _ => 5,
+        };
+        let nibbles = groups.iter()
+            .flat_map(|&(count, i)| std::iter::repeat(i as u32 + 1).take(count as usize))
+            .fold(0, |acc, v| (acc << 4) | v);
+        LowRank(((5 - category) << 20) | (0xFFFFF - nibbles))
+    }
+
+    /// raw value, the higher the better
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// High (no pair), Pair, Pair2, Three, Full or Four
+    pub fn category(&self) -> CardsType {
+        CATEGORIES[5 - (self.0 >> 20) as usize]
+    }
+
+    /// the 5 card values, ordered by importance
+    ///
+    /// e.g. `[8,5,4,2,A]`, `[3,3,K,5,A]`.
+    pub fn values(&self) -> [Value;5] {
+        let nibbles = 0xFFFFF - (self.0 & 0xFFFFF);
+        let mut values = [Value::Two;5];
+        for (i, v) in values.iter_mut().enumerate() {
+            *v = low_value(((nibbles >> ((4-i)*4)) & 0xF) as u8 - 1);
+        }
+        values
+    }
+
+    /// no pair and the highest card is not above `max`, e.g. eight-or-better
+    pub fn qualifies(&self, max: Value) -> bool {
+        self.category() == CardsType::High && low_index(self.values()[0]) <= low_index(max)
+    }
+}
+
+/// Ace-to-five lowball evaluator, for Razz and the low half of hi-lo games
+///
+/// Evaluates the best 5 cards of 5 or more cards. With a qualifier, hands
+/// which are not qualified get None.
+///
+/// Example:
+/// ```
+/// use pokerlib::LowEvaluator;
+/// use pokerlib::card::{parse_cards, Value};
+///
+/// let evaluator = pokerlib::AceFiveEvaluator::eight_or_better();
+/// let cards: Vec<_> = parse_cards("Ks8h6d5c3hAsKd").unwrap();
+/// let rank = evaluator.simple_eval_low(&cards).unwrap();
+/// assert_eq!(rank.values(), [Value::Eight, Value::Six, Value::Five, Value::Three, Value::Ace]);
+/// assert_eq!(evaluator.simple_eval_low(&parse_cards::<Vec<_>>("Ks9h6d5c3hAsKd").unwrap()), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AceFiveEvaluator {
+    qualifier: Option<Value>,
+}
+
+impl AceFiveEvaluator {
+    /// without qualifier
+    pub fn new() -> AceFiveEvaluator {
+        AceFiveEvaluator{qualifier: None}
+    }
+
+    /// only no-pair hands with the highest card not above `max` are qualified
+    pub fn with_qualifier(max: Value) -> AceFiveEvaluator {
+        AceFiveEvaluator{qualifier: Some(max)}
+    }
+
+    /// eight-or-better qualifier, for Omaha/8 and Stud/8
+    pub fn eight_or_better() -> AceFiveEvaluator {
+        AceFiveEvaluator::with_qualifier(Value::Eight)
+    }
+
+    pub fn qualifier(&self) -> Option<Value> {
+        self.qualifier
+    }
+
+    /// best low and the 5 cards made it
+    pub fn best_low_cards(&self, cards: &[Card]) -> Option<(LowRank, [Card;5])> {
+        let rank = self.simple_eval_low(cards)?;
+        let mut best = [cards[0];5];
+        let mut used = vec![false; cards.len()];
+        for (i, &value) in rank.values().iter().enumerate() {
+            let pos = (0..cards.len()).find(|&j| !used[j] && cards[j].value() == value)?;
+            used[pos] = true;
+            best[i] = cards[pos];
+        }
+        Some((rank, best))
+    }
+}
+
+impl LowEvaluator for AceFiveEvaluator {
+    /// ace-low value index: A=0, 2=1, ..., K=12
+    type CardType = u8;
+    type Rank = LowRank;
+
+    /// the joker is 13, then `eval_low` is None
+    fn make_card(&self, card: &Card) -> u8 {
+        if card.is_joker() {
+            return JOKER_INDEX;
+        }
+        low_index(card.value())
+    }
+
+    fn eval_low(&self, cards: &[u8]) -> Option<LowRank> {
+        if cards.len() < 5 || cards.contains(&JOKER_INDEX) {
+            return None;
+        }
+        let mut seen = 0u16;
+        for &i in cards.iter() {
+            seen |= 1 << i;
+        }
+        let rank = if seen.count_ones() >= 5 {
+            // 5 种以上牌值: 取最小的 5 个, 一定是最好的无对子组合
+            let lowest: Vec<u8> = (0..13).filter(|&i| seen & (1 << i) != 0).take(5).collect();
+            LowRank::with_low_indexes(&lowest)
+        }else{
+            let mut indexes = [0;5];
+            let mut five = [0;5];
+            let mut best = LowRank(0);
+            let mut it = CombinationIter::new(cards.len(), 5);
+            while it.move_next(&mut indexes) {
+                for (v, &i) in five.iter_mut().zip(indexes.iter()) {
+                    *v = cards[i];
+                }
+                best = best.max(LowRank::with_low_indexes(&five));
+            }
+            best
+        };
+        match self.qualifier {
+            Some(max) if !rank.qualifies(max) => None,
+            _ => Some(rank),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn low(evaluator: &AceFiveEvaluator, s: &str) -> Option<LowRank> {
+        evaluator.simple_eval_low(&parse_cards::<Vec<Card>>(s).unwrap())
+    }
+
+    #[test]
+    fn test_ace_five_order(){
+        let evaluator = AceFiveEvaluator::new();
+        // 同花顺子不影响
+        assert_eq!(low(&evaluator, "5s4s3s2sAs"), Some(LowRank::BEST));
+        assert!(low(&evaluator, "6s4d3c2hAs") > low(&evaluator, "6s5d3c2hAs"));
+        assert!(low(&evaluator, "KsQdJcTh9s") > low(&evaluator, "AsAd2c3h4s"));
+        assert!(low(&evaluator, "AsAd2c3h4s") > low(&evaluator, "2s2d3c4h5s"));
+        assert!(low(&evaluator, "AsAd2c2h4s") < low(&evaluator, "KsKdQcJhTs"));
+
+        // 两对好于三条
+        let rank = low(&evaluator, "3s3d3cKhKs9d").unwrap();
+        assert_eq!(rank.category(), CardsType::Pair2);
+        assert_eq!(rank.values(), [Value::Kine, Value::Kine, Value::Three, Value::Three, Value::Nign]);
+
+        let rank = low(&evaluator, "KsKdKcKh2s2d2c").unwrap();
+        assert_eq!(rank.category(), CardsType::Full);
+        assert_eq!(rank.values(), [Value::Two, Value::Two, Value::Two, Value::Kine, Value::Kine]);
+        assert_eq!(low(&evaluator, "KsKdKc2s"), None);
+        assert_eq!(low(&evaluator, "JkKsKdKc2s"), None);
+    }
+
+    #[test]
+    fn test_eight_or_better(){
+        let evaluator = AceFiveEvaluator::eight_or_better();
+        assert!(low(&evaluator, "8s7d6c5hAs").is_some());
+        assert_eq!(low(&evaluator, "9s7d6c5hAs"), None);
+        assert_eq!(low(&evaluator, "AsAd2c3h4s"), None);
+
+        let cards = parse_cards::<Vec<Card>>("Ks4h2dAc4s3d8h").unwrap();
+        let (rank, best) = evaluator.best_low_cards(&cards).unwrap();
+        assert_eq!(rank.values(), [Value::Eight, Value::Four, Value::Three, Value::Two, Value::Ace]);
+        assert_eq!(best.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["8h", "4h", "3d", "2d", "Ac"]);
+        assert_eq!(AceFiveEvaluator::new().best_low_cards(&cards).map(|(r, _)| r), Some(rank));
+    }
+}
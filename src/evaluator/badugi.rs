@@ -0,0 +1,163 @@
+use crate::card::{Card, CardIndexPolicy, Value};
+use crate::LowEvaluator;
+
+/// Badugi rank, the higher the better
+///
+/// The playing cards have different values and different suits, aces are low.
+/// More playing cards is better (a 4 cards badugi beats any 3 cards hand),
+/// then the lower highest card, and so on.
+///
+/// format: cards count << 16 | (0xFFFF - 4 value nibbles), nibbles are the values
+/// from high to low, Ace=1 ... Kine=13, padded with 0.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct BadugiRank(u32);
+
+// ace-low 牌值 0-12 的 bit mask 转为 rank
+fn pack(count: u32, value_mask: u16) -> BadugiRank {
+    let nibbles = (0..13u32).rev()
+        .filter(|&v| value_mask & (1 << v) != 0)
+        .fold(0, |acc, v| (acc << 4) | (v + 1));
+    BadugiRank((count << 16) | (0xFFFF - (nibbles << (4 * (4 - count)))))
+}
+
+impl BadugiRank {
+    /// the best badugi: 4-3-2-A of four suits
+    pub const BEST: BadugiRank = BadugiRank((4 << 16) | (0xFFFF - 0x4321));
+
+    /// raw value, the higher the better
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// playing cards count, 1-4
+    pub fn card_count(&self) -> usize {
+        (self.0 >> 16) as usize
+    }
+
+    /// 4 playing cards
+    pub fn is_badugi(&self) -> bool {
+        self.card_count() == 4
+    }
+
+    /// playing card values from high to low, e.g. `[8,5,3,A]`
+    pub fn values(&self) -> Vec<Value> {
+        let nibbles = 0xFFFF - (self.0 & 0xFFFF);
+        (0..self.card_count())
+            .map(|i| (nibbles >> ((3 - i) * 4)) & 0xF)
+            .map(|v| Value::from_index(((v + 11) % 13) as u8))
+            .collect()
+    }
+}
+
+/// Badugi evaluator
+///
+/// Evaluates the best hand of 1-8 cards (4 cards in Badugi).
+///
+/// Example:
+/// ```
+/// use pokerlib::LowEvaluator;
+/// use pokerlib::card::{parse_cards, Value};
+/// use pokerlib::evaluator::badugi::BadugiEvaluator;
+///
+/// let evaluator = BadugiEvaluator::new();
+/// let cards: Vec<_> = parse_cards("As3s5d7h").unwrap();
+/// let (rank, playing) = evaluator.best_cards(&cards).unwrap();
+/// // 3s doesn't play: same suit as As
+/// assert_eq!(rank.values(), vec![Value::Seven, Value::Five, Value::Ace]);
+/// assert_eq!(playing.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BadugiEvaluator {}
+
+impl BadugiEvaluator {
+    pub fn new() -> BadugiEvaluator {
+        BadugiEvaluator{}
+    }
+
+    /// best rank and the playing cards, from high to low
+    pub fn best_cards(&self, cards: &[Card]) -> Option<(BadugiRank, Vec<Card>)> {
+        let inner_cards: Vec<u8> = cards.iter().map(|x| self.make_card(x)).collect();
+        let (rank, subset) = best(&inner_cards)?;
+        let mut playing: Vec<Card> = (0..cards.len())
+            .filter(|&i| subset & (1 << i) != 0)
+            .map(|i| cards[i])
+            .collect();
+        playing.sort_by_key(|card| std::cmp::Reverse((card.value_index() + 1) % 13));
+        Some((rank, playing))
+    }
+}
+
+// 枚举所有牌值和花色都不同的子集, 返回最好的 rank 和子集 mask
+fn best(cards: &[u8]) -> Option<(BadugiRank, u32)> {
+    // 52 是 joker
+    if cards.is_empty() || cards.len() > 8 || cards.iter().any(|&c| c >= 52) {
+        return None;
+    }
+    let mut best: Option<(BadugiRank, u32)> = None;
+    for subset in 1..(1u32 << cards.len()) {
+        if subset.count_ones() > 4 {
+            continue;
+        }
+        let mut value_mask = 0u16;
+        let mut suit_mask = 0u8;
+        let valid = (0..cards.len()).filter(|&i| subset & (1 << i) != 0).all(|i| {
+            let (value, suit) = ((cards[i] / 4 + 1) % 13, cards[i] % 4);
+            let valid = value_mask & (1 << value) == 0 && suit_mask & (1 << suit) == 0;
+            value_mask |= 1 << value;
+            suit_mask |= 1 << suit;
+            valid
+        });
+        if valid {
+            let rank = pack(subset.count_ones(), value_mask);
+            if best.map_or(true, |(b, _)| rank > b) {
+                best = Some((rank, subset));
+            }
+        }
+    }
+    best
+}
+
+impl LowEvaluator for BadugiEvaluator {
+    /// card index with `CardIndexPolicy::GroupByValue`
+    type CardType = u8;
+    type Rank = BadugiRank;
+
+    /// the joker is 52, then `eval_low` is None
+    fn make_card(&self, card: &Card) -> u8 {
+        card.card_index(CardIndexPolicy::GroupByValue)
+    }
+
+    fn eval_low(&self, cards: &[u8]) -> Option<BadugiRank> {
+        best(cards).map(|(rank, _)| rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn rank(s: &str) -> BadugiRank {
+        BadugiEvaluator::new().simple_eval_low(&parse_cards::<Vec<Card>>(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_badugi_order(){
+        assert_eq!(rank("As2h3c4d"), BadugiRank::BEST);
+        assert!(rank("KsQhJcTd") > rank("As2h3c3d"));
+        assert!(rank("As2h3c5d") > rank("As2h4c5d"));
+        assert!(rank("As2h3c") > rank("As2h4c"));
+        assert!(rank("As2s3s4s") == rank("Ac"));
+        assert!(rank("2s3h4c") > rank("As2s3h4h"));
+
+        let r = rank("7s7h2c2d");
+        assert_eq!(r.card_count(), 2);
+        assert_eq!(r.values(), vec![Value::Seven, Value::Two]);
+
+        let (r, cards) = BadugiEvaluator::new().best_cards(&parse_cards::<Vec<Card>>("Kd8s4hAh3c").unwrap()).unwrap();
+        assert!(r.is_badugi());
+        assert_eq!(cards.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["Kd", "8s", "3c", "Ah"]);
+        assert_eq!(BadugiEvaluator::new().simple_eval_low(&parse_cards::<Vec<Card>>("JkAs2h3c").unwrap()), None);
+    }
+}
@@ -0,0 +1,97 @@
+// cactuskev 查表求值, 只依赖 std, build.rs 也用它生成 twoplustwo 表格
+
+// 一些表格
+const FLUSHES: [u16;0x1F01] = include!("tbl_flushes");
+const UNIQUE5: [u16;0x1F01] = include!("tbl_unique5");
+//const OTHER_PRODUCTS: [u32;4888] = include!("tbl_other_products");
+//const OTHER_VALUES: [u16;4888] = include!("tbl_other_values");
+const HASH_ADJUST: [u16;512] = include!("tbl_hash_adjust");
+const HASH_VALUES: [u16;8191] = include!("tbl_hash_values");
+
+// perfect hash
+pub fn find_fast(u: u32) -> u32 {
+    let mut u = u as u64 + 0xe91aaa35;
+    u ^= u >> 16;
+    u += u << 8;
+    u ^= ((u as u32) >> 4) as u64;
+    let b: u64 = (u >> 8) & 0x1FF;
+    let a: u64 = (u + (u << 2) & 0xFFFFFFFF) >> 19;
+    let r: u32 = (a as u32) ^ (HASH_ADJUST[b as usize] as u32);
+    r
+}
+
+fn eval_five_ex(input_cards: &[u32], max_cmp_type: u32) -> u32 {
+    if input_cards.len() != 5 {
+        return 0;
+    }
+
+    let q: usize = (0..5).fold(0, |a, i| a | input_cards[i] as usize) >> 16;
+    if 0 != (0..5).fold(0xF000, |a, i| a & input_cards[i]) {
+        // straight-flush/flush
+        return 7463-FLUSHES[q] as u32;
+    }
+    if max_cmp_type >= 9 {
+        return 0;
+    }
+
+    let unique = UNIQUE5[q];
+    if unique != 0 {
+        return 7463-unique as u32;
+    }
+
+    let lookup = find_fast(
+        (0..5).fold(1, |acc, b| acc * (input_cards[b]&0xFF)) as u32
+    );
+    return 7463-HASH_VALUES[lookup as usize] as u32;
+}
+
+fn eval_five(input_cards: &[u32]) -> u32 {
+    eval_five_ex(input_cards, 0)
+}
+
+/// eval value of 5-7 cards, 0 for other counts
+pub fn eval_cards(input_cards: &[u32]) -> u32 {
+    let len = input_cards.len();
+    if 5 == len {
+        return eval_five(input_cards);
+    }
+
+    let mut cards: [u32;7] = [0;7];
+    for i in 0..len {
+        cards[i] = input_cards[i];
+    }
+
+    let mut max_cmp_value = 0;
+    // 6
+    if 6 == len{
+        max_cmp_value = eval_five(&cards[0..5]);
+        for i in 0..5 {
+            cards.swap(i,5);
+            let cmp_value = eval_five_ex(&cards[0..5], max_cmp_value>>20);
+            if cmp_value > max_cmp_value {
+                max_cmp_value = cmp_value;
+            }
+            cards.swap(i,5);
+        }
+    }else if 7 == len{
+        for &(c1, c2) in &[
+            (0,1),(0,2),(0,3),(0,4),(5,0),(0,6),
+            (1,2),(1,3),(1,4),(5,1),(1,6),
+            (2,3),(2,4),(5,2),(2,6),
+            (3,4),(5,3),(3,6),
+            (5,4),(4,6),
+            (5,6)
+        ] {
+            cards.swap(c1, 5);
+            cards.swap(c2, 6);
+            let cmp_value = eval_five_ex(&cards[0..5], max_cmp_value>>20);
+            if cmp_value > max_cmp_value {
+                max_cmp_value = cmp_value;
+            }
+            cards.swap(c2, 6);
+            cards.swap(c1, 5);
+        }
+    }
+
+    return max_cmp_value;
+}
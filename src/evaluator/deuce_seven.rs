@@ -0,0 +1,172 @@
+use crate::card::{Card, Value};
+use crate::cards_type::CardsType;
+use crate::{Evaluator, LowEvaluator};
+use crate::evaluator::NativeEvaluator;
+use crate::tools::combination::CombinationIter;
+
+// 5-4-3-2-A 顺子在 NativeEvaluator 中的值: 只记录最大牌 5
+const WHEEL: u32 = 4 << 16;
+// make_card 给 joker 的值, native 的牌不会是 0
+const JOKER: u32 = 0;
+// 2-7 中 A 是大牌, 5-4-3-2-A 只是 A 高: A,5,4,3,2
+const ACE_HIGH_WHEEL: u32 = 0xD4321;
+
+/// 2-7 low rank, the higher the better
+///
+/// It's the inverted high hand order, aces are always high:
+/// 7-5-4-3-2 offsuit is the best, 5-4-3-2-A is an ace-high hand,
+/// straights and flushes count against you.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct DeuceSevenRank(u32);
+
+impl DeuceSevenRank {
+    /// the best 2-7 low: 7-5-4-3-2 offsuit
+    pub const BEST: DeuceSevenRank = DeuceSevenRank(!((1 << 20) | 0x64321));
+
+    // NativeEvaluator 的 5 张牌值, 把 5-4-3-2-A 顺子改成 A 高
+    fn with_native_value(value: u32) -> DeuceSevenRank {
+        let value = match (value >> 20, value & 0xFFFFF) {
+            (5, WHEEL) => (1 << 20) | ACE_HIGH_WHEEL,
+            (9, WHEEL) => (6 << 20) | ACE_HIGH_WHEEL,
+            _ => value,
+        };
+        DeuceSevenRank(!value)
+    }
+
+    // 对应的 NativeEvaluator 值 (A 高的 5-4-3-2-A 不是合法的 native 值)
+    #[inline]
+    fn native_value(&self) -> u32 {
+        !self.0
+    }
+
+    /// raw value, the higher the better
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// cards type as high hand, 5-4-3-2-A is High or Flush
+    pub fn category(&self) -> CardsType {
+        NativeEvaluator::new().eval_value_type(self.native_value()).unwrap()
+    }
+
+    /// the 5 card values, ordered by importance, e.g. `[7,5,4,3,2]`, `[A,5,4,3,2]`
+    pub fn values(&self) -> [Value;5] {
+        NativeEvaluator::new().unpack_native_value(self.native_value()).unwrap().1
+    }
+}
+
+/// Deuce-to-seven lowball evaluator, for 2-7 Triple Draw and Single Draw
+///
+/// Evaluates the best (lowest) 5 cards of 5 or more cards with `NativeEvaluator`,
+/// and inverts the high hand order.
+///
+/// Example:
+/// ```
+/// use pokerlib::LowEvaluator;
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::evaluator::deuce_seven::{DeuceSevenEvaluator, DeuceSevenRank};
+///
+/// let evaluator = DeuceSevenEvaluator::new();
+/// let number_one = evaluator.simple_eval_low(&parse_cards::<Vec<_>>("7s5d4c3h2s").unwrap()).unwrap();
+/// let wheel = evaluator.simple_eval_low(&parse_cards::<Vec<_>>("5s4d3c2hAs").unwrap()).unwrap();
+/// assert_eq!(number_one, DeuceSevenRank::BEST);
+/// assert!(number_one > wheel);
+/// ```
+pub struct DeuceSevenEvaluator {
+    native: NativeEvaluator,
+}
+
+impl DeuceSevenEvaluator {
+    pub fn new() -> DeuceSevenEvaluator {
+        DeuceSevenEvaluator{native: NativeEvaluator::new()}
+    }
+
+    /// best low and the 5 cards made it
+    pub fn best_low_cards(&self, cards: &[Card]) -> Option<(DeuceSevenRank, [Card;5])> {
+        let inner_cards: Vec<u32> = cards.iter().map(|x| self.make_card(x)).collect();
+        let (rank, indexes) = self.best(&inner_cards)?;
+        let mut best = [cards[0];5];
+        for (card, &i) in best.iter_mut().zip(indexes.iter()) {
+            *card = cards[i];
+        }
+        Some((rank, best))
+    }
+
+    fn best(&self, cards: &[u32]) -> Option<(DeuceSevenRank, [usize;5])> {
+        if cards.len() < 5 || cards.contains(&JOKER) {
+            return None;
+        }
+        let mut indexes = [0;5];
+        let mut five = [0;5];
+        let mut best: Option<(DeuceSevenRank, [usize;5])> = None;
+        let mut it = CombinationIter::new(cards.len(), 5);
+        while it.move_next(&mut indexes) {
+            for (c, &i) in five.iter_mut().zip(indexes.iter()) {
+                *c = cards[i];
+            }
+            let rank = DeuceSevenRank::with_native_value(self.native.eval(&five));
+            if best.map_or(true, |(b, _)| rank > b) {
+                best = Some((rank, indexes));
+            }
+        }
+        best
+    }
+}
+
+impl Default for DeuceSevenEvaluator {
+    fn default() -> DeuceSevenEvaluator {
+        DeuceSevenEvaluator::new()
+    }
+}
+
+impl LowEvaluator for DeuceSevenEvaluator {
+    /// same as `NativeEvaluator`
+    type CardType = u32;
+    type Rank = DeuceSevenRank;
+
+    /// the joker is 0, then `eval_low` is None
+    fn make_card(&self, card: &Card) -> u32 {
+        if card.is_joker() {
+            return JOKER;
+        }
+        self.native.make_card(card)
+    }
+
+    fn eval_low(&self, cards: &[u32]) -> Option<DeuceSevenRank> {
+        self.best(cards).map(|(rank, _)| rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn low(s: &str) -> DeuceSevenRank {
+        DeuceSevenEvaluator::new().simple_eval_low(&parse_cards::<Vec<Card>>(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_deuce_seven_order(){
+        assert_eq!(low("7s5d4c3h2s"), DeuceSevenRank::BEST);
+        assert!(low("7s5d4c3h2s") > low("7s6d4c3h2s"));
+        assert!(low("8s5d4c3h2s") > low("8s6d5c4h2s"));
+        // 同花, 顺子都算大牌
+        assert!(low("KsQdJc9h8s") > low("7s5s4s3s2s"));
+        assert!(low("AsKdQcJh9s") > low("6s5d4c3h2s"));
+        assert!(low("2s2d3c4h5s") < low("AsKdQcJh9s"));
+
+        let wheel = low("5s4d3c2hAs");
+        assert_eq!(wheel.category(), CardsType::High);
+        assert_eq!(wheel.values(), [Value::Ace, Value::Five, Value::Four, Value::Three, Value::Two]);
+        assert!(wheel > low("2s2d3c4h5s") && wheel < low("KsQdJc9h8s"));
+        assert_eq!(low("5s4s3s2sAs").category(), CardsType::Flush);
+
+        let (rank, cards) = DeuceSevenEvaluator::new()
+            .best_low_cards(&parse_cards::<Vec<Card>>("7s6s5s4s3s2dKd").unwrap()).unwrap();
+        assert_eq!(rank, DeuceSevenRank::BEST);
+        assert_eq!(cards.iter().map(|c| c.to_string()).collect::<Vec<_>>(), ["7s", "5s", "4s", "3s", "2d"]);
+        assert_eq!(DeuceSevenEvaluator::new().simple_eval_low(&parse_cards::<Vec<Card>>("Jk7s5d4c3h2s").unwrap()), None);
+    }
+}
@@ -1,8 +1,17 @@
 pub mod native;
 pub mod cactuskev;
 pub mod twoplustwo;
+pub mod ace_five;
+pub mod badugi;
+pub mod deuce_seven;
+pub mod short_deck;
+pub mod wild;
 
 pub use native::NativeEvaluator;
 pub use cactuskev::CactusKevEvaluator;
 pub use twoplustwo::TwoPlusTwoEvaluator;
-
+pub use short_deck::ShortDeckEvaluator;
+pub use wild::WildEvaluator;
+pub use ace_five::{AceFiveEvaluator, LowRank};
+pub use deuce_seven::{DeuceSevenEvaluator, DeuceSevenRank};
+pub use badugi::{BadugiEvaluator, BadugiRank};
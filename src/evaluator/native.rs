@@ -1,6 +1,7 @@
-use crate::Evaluator;
+use crate::{Evaluator, IncrementalEvaluator};
 use crate::card;
 use crate::cards_type::CardsType;
+use crate::hand_rank::HandRank;
 
 /// a native evaluator
 ///
@@ -19,34 +20,206 @@ impl NativeEvaluator {
         card & 0xF
     }
 
-    /// unpack a eval value
+    /// unpack a eval value from its packed nibbles, without any table
     ///
     /// result:
     ///   - cardstype
     ///   - result card's values
-    pub fn unpack_eval_value(&self, eval_value: u32) -> Option<(CardsType, Vec<card::Value>)>{
-        let ctype = self.eval_value_type(eval_value);
-        if ctype.is_none(){
-            return None;
-        }
-        let ctype = ctype.unwrap();
-        let mut values: Vec<card::Value> = Vec::new();
+    pub(crate) fn unpack_native_value(&self, eval_value: u32) -> Option<(CardsType, [card::Value;5])>{
+        let ctype = self.eval_value_type(eval_value)?;
+        let mut values = [card::Value::Two;5];
         let space_diff = if ctype == CardsType::Straight || ctype == CardsType::StraightFlush {1} else {0};
         let mut last_v = 0;
-        for i in  0..5 {
+        for (i, value) in values.iter_mut().enumerate() {
             let v = ((eval_value>>((4-i)*4)) & 0xF) as u8;
             if v != 0 {
-                values.push(card::Value::from_index(v - 1));
                 last_v = v;
             }else{
                 last_v = ((last_v-1+13-space_diff) % 13) + 1;
-                values.push(card::Value::from_index(last_v - 1));
             }
+            *value = card::Value::from_index(last_v - 1);
         }
         Some((ctype, values))
     }
 }
 
+/// state of `NativeEvaluator` as `IncrementalEvaluator`
+///
+/// value bitmask of every suit and the cards count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NativeState {
+    suit_masks: [u16;4],
+    count: u8,
+}
+
+// 最大的几个牌值, 每个 4 bit (Two=1), 大的在前
+#[inline]
+fn top_values(mask: u16, n: u32) -> u32 {
+    let mut mask = mask;
+    let mut values = 0;
+    for _ in 0..n {
+        let v = 15 - mask.leading_zeros();
+        values = (values << 4) | (v + 1);
+        mask &= !(1 << v);
+    }
+    values
+}
+
+// 顺子最大的牌值 (Two=1), A-5 是 4
+#[inline]
+fn straight_top(mask: u16) -> Option<u32> {
+    let bits = ((mask as u32) << 1) | ((mask as u32 >> 12) & 1);
+    (4..=13).rev().find(|&top| (bits >> (top - 4)) & 0x1F == 0x1F)
+}
+
+impl IncrementalEvaluator for NativeEvaluator {
+    type State = NativeState;
+
+    #[inline]
+    fn start(&self) -> NativeState {
+        NativeState::default()
+    }
+
+    #[inline]
+    fn add_card(&self, state: NativeState, card: u32) -> NativeState {
+        let value_index = self.card_value(card) - 1;
+        let mut state = state;
+        state.suit_masks[(card >> 30) as usize] |= 1 << value_index;
+        state.count += 1;
+        state
+    }
+
+    fn finish(&self, state: NativeState) -> u32 {
+        if state.count > 7 || state.count < 5 {
+            return 0;
+        }
+        self.eval_suit_masks(state.suit_masks)
+    }
+}
+
+// eval_batch 每次并行计算的手数
+const LANES: usize = 16;
+
+// 最高位的位置, 0 时返回 0
+#[inline(always)]
+fn top_bit(mask: u32) -> u32 {
+    31 - (mask | 1).leading_zeros()
+}
+
+// 无分支的 top_values, 结果只在 mask 至少有 n 位时有意义
+#[inline(always)]
+fn lane_top_values(mask: u32, n: u32) -> u32 {
+    let mut mask = mask;
+    let mut values = 0;
+    for _ in 0..n {
+        let v = top_bit(mask);
+        values = (values << 4) | ((v + 1) & 0u32.wrapping_sub((mask != 0) as u32));
+        mask &= !(1 << v);
+    }
+    values
+}
+
+// 无分支的 straight_top, 没有顺子时为 0
+#[inline(always)]
+fn lane_straight_top(mask: u32) -> u32 {
+    let bits = (mask << 1) | ((mask >> 12) & 1);
+    let runs = bits & (bits >> 1) & (bits >> 2) & (bits >> 3) & (bits >> 4);
+    (top_bit(runs) + 4) & 0u32.wrapping_sub((runs != 0) as u32)
+}
+
+// 条件为真时取 value, 否则 0
+#[inline(always)]
+fn select(condition: bool, value: u32) -> u32 {
+    value & 0u32.wrapping_sub(condition as u32)
+}
+
+// 一手牌的 eval 值: 算出所有牌型的候选值, 不成立的为 0, 取最大的
+//
+// 没有分支, 循环 LANES 手牌时可以向量化. 7 张牌里同花不会和四条/葫芦同时出现,
+// 所以和 eval_suit_masks 的结果一致.
+#[inline(always)]
+fn eval_lane(s: u32, h: u32, c: u32, d: u32) -> u32 {
+    let all = s | h | c | d;
+    let two_more = (s & h) | (s & c) | (s & d) | (h & c) | (h & d) | (c & d);
+    let three_more = (s & h & c) | (s & h & d) | (s & c & d) | (h & c & d);
+    let fours = s & h & c & d;
+    let threes = three_more & !fours;
+    let twos = two_more & !three_more;
+    let ones = all & !two_more;
+
+    let flush = select(s.count_ones() >= 5, s) | select(h.count_ones() >= 5, h)
+        | select(c.count_ones() >= 5, c) | select(d.count_ones() >= 5, d);
+    let straight_flush = lane_straight_top(flush);
+    let straight = lane_straight_top(all);
+    let four = top_bit(fours);
+    let three = top_bit(threes);
+    let full_pair = (threes & !(1 << three)) | twos;
+    let high_pair = top_bit(twos);
+    let low_pair = top_bit(twos & !(1 << high_pair));
+
+    let mut best = (1 << 20) | lane_top_values(ones, 5);
+    best = best.max(select(twos != 0, (2 << 20) | ((high_pair + 1) << 16) | lane_top_values(ones, 3)));
+    best = best.max(select(twos.count_ones() >= 2, (3 << 20) | ((high_pair + 1) << 16) | ((low_pair + 1) << 8)
+        | lane_top_values((twos | ones) & !(1 << high_pair) & !(1 << low_pair), 1)));
+    best = best.max(select(threes != 0, (4 << 20) | ((three + 1) << 16) | lane_top_values(ones, 2)));
+    best = best.max(select(straight != 0, (5 << 20) | (straight << 16)));
+    best = best.max(select(flush != 0, (6 << 20) | lane_top_values(flush, 5)));
+    best = best.max(select(threes != 0 && full_pair != 0, (7 << 20) | ((three + 1) << 16) | (lane_top_values(full_pair, 1) << 4)));
+    best = best.max(select(fours != 0, (8 << 20) | ((four + 1) << 16) | lane_top_values(all & !(1 << four), 1)));
+    best.max(select(straight_flush != 0, (9 << 20) | (straight_flush << 16)))
+}
+
+impl NativeEvaluator {
+    /// eval by the value bitmask of every suit, 5-7 different cards
+    fn eval_suit_masks(&self, suit_masks: [u16;4]) -> u32 {
+        if let Some(&mask) = suit_masks.iter().find(|m| m.count_ones() >= 5) {
+            return match straight_top(mask) {
+                Some(top) => (9 << 20) | (top << 16),
+                None => (6 << 20) | top_values(mask, 5),
+            };
+        }
+
+        // 按出现次数分组的牌值
+        let [s, h, c, d] = suit_masks;
+        let all = s | h | c | d;
+        let two_more = (s & h) | (s & c) | (s & d) | (h & c) | (h & d) | (c & d);
+        let three_more = (s & h & c) | (s & h & d) | (s & c & d) | (h & c & d);
+        let fours = s & h & c & d;
+        let threes = three_more & !fours;
+        let twos = two_more & !three_more;
+        let ones = all & !two_more;
+
+        if fours != 0 {
+            let four = 15 - fours.leading_zeros();
+            return (8 << 20) | ((four + 1) << 16) | top_values(all & !(1 << four), 1);
+        }
+        if threes != 0 {
+            let three = 15 - threes.leading_zeros();
+            let rest = (threes & !(1 << three)) | twos;
+            if rest != 0 {
+                return (7 << 20) | ((three + 1) << 16) | (top_values(rest, 1) << 4);
+            }
+        }
+        if let Some(top) = straight_top(all) {
+            return (5 << 20) | (top << 16);
+        }
+        if threes != 0 {
+            return (4 << 20) | (top_values(threes, 1) << 16) | top_values(ones, 2);
+        }
+        if twos.count_ones() >= 2 {
+            let pairs = top_values(twos, 2);
+            let low_pair = (pairs & 0xF) - 1;
+            let high_pair = (pairs >> 4) - 1;
+            let kicker = top_values((twos | ones) & !(1 << low_pair) & !(1 << high_pair), 1);
+            return (3 << 20) | ((pairs >> 4) << 16) | ((pairs & 0xF) << 8) | kicker;
+        }
+        if twos != 0 {
+            return (2 << 20) | (top_values(twos, 1) << 16) | top_values(ones, 3);
+        }
+        (1 << 20) | top_values(ones, 5)
+    }
+}
+
 impl Evaluator for NativeEvaluator {
     /// card format:
     /// u32
@@ -86,6 +259,36 @@ impl Evaluator for NativeEvaluator {
         }
     }
 
+    fn hand_rank(&self, eval_value: u32) -> Option<HandRank> {
+        let (ctype, values) = self.unpack_native_value(eval_value)?;
+        HandRank::with_values(ctype, &values)
+    }
+
+    /// lane-parallel backend: `LANES` hands are evaluated together in structure-of-arrays
+    /// layout (one value bitmask array per suit), without branches, so the loops vectorize
+    fn eval_batch(&self, cards: &[u32], cards_per_hand: usize, out: &mut [u32]) {
+        assert!((5..=7).contains(&cards_per_hand), "eval_batch: {} cards per hand", cards_per_hand);
+        assert_eq!(cards.len(), out.len() * cards_per_hand, "eval_batch: cards and out length mismatch");
+        let mut values = [0u32; LANES];
+        for (hands, out) in cards.chunks(LANES * cards_per_hand).zip(out.chunks_mut(LANES)) {
+            let mut masks = [[0u32; LANES]; 4];
+            for (lane, hand) in hands.chunks_exact(cards_per_hand).enumerate() {
+                for &card in hand {
+                    // 牌值 bit 在 17..30, 花色在最高 2 位
+                    let bit = (card >> 17) & 0x1FFF;
+                    let suit = card >> 30;
+                    for (i, suit_masks) in masks.iter_mut().enumerate() {
+                        suit_masks[lane] |= select(suit == i as u32, bit);
+                    }
+                }
+            }
+            for (lane, value) in values.iter_mut().enumerate() {
+                *value = eval_lane(masks[0][lane], masks[1][lane], masks[2][lane], masks[3][lane]);
+            }
+            out.copy_from_slice(&values[..out.len()]);
+        }
+    }
+
     fn eval(&self, input_cards: &[u32]) -> u32 {
         if input_cards.len() > 7 || input_cards.len() < 5 {
             return 0;
@@ -180,7 +383,9 @@ impl Evaluator for NativeEvaluator {
 
         // 葫芦 7
         if three != 0 && twos != 0{
-            return (7 << 20) | (three << 16) | (twos << 4)
+            // 有两个对子时取大的那个
+            let two = if (twos >> 4) != 0 {twos >> 4} else {twos};
+            return (7 << 20) | (three << 16) | (two << 4)
         }
         
         // 顺子 5
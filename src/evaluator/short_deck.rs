@@ -0,0 +1,218 @@
+use crate::Evaluator;
+use crate::card::{self, CardIndexPolicy};
+use crate::cards_type::CardsType;
+use crate::hand_rank::HandRank;
+
+// A,6,7,8,9: 短牌中 A 可以当作 5 组成最小的顺子
+const ACE_SIX_STRAIGHT: u16 = 0x10F0;
+
+/// Short-deck (6+) hold'em evaluator
+///
+/// For the 36 cards deck without 2-5 (see `Deck::short_deck`):
+/// flush beats full house, A-6-7-8-9 is the lowest straight.
+/// Some rule sets rank three of a kind above straight, use `trips_beat_straight`.
+///
+/// eval value: category order << 20 | 5 card values (4 bit each, Two=1 ... Ace=13).
+/// `HandRank` is not supported: `hand_rank` is always None because the order differs,
+/// use `eval_value_type` and `unpack_eval_value` instead.
+///
+/// Example:
+/// ```
+/// use pokerlib::Evaluator;
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::evaluator::ShortDeckEvaluator;
+///
+/// let evaluator = ShortDeckEvaluator::new();
+/// let flush = evaluator.simple_eval(&parse_cards::<Vec<_>>("As9s8s7sJs").unwrap());
+/// let full = evaluator.simple_eval(&parse_cards::<Vec<_>>("AsAdAcKsKd").unwrap());
+/// assert!(flush > full);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShortDeckEvaluator {
+    trips_beat_straight: bool,
+}
+
+impl ShortDeckEvaluator {
+    /// straight beats three of a kind
+    pub fn new() -> ShortDeckEvaluator {
+        ShortDeckEvaluator{trips_beat_straight: false}
+    }
+
+    /// three of a kind beats straight
+    pub fn trips_beat_straight() -> ShortDeckEvaluator {
+        ShortDeckEvaluator{trips_beat_straight: true}
+    }
+
+    pub fn is_trips_beat_straight(&self) -> bool {
+        self.trips_beat_straight
+    }
+
+    /// cards types from worst to best
+    pub fn cards_types(&self) -> [CardsType;9] {
+        let (fifth, sixth) = if self.trips_beat_straight {
+            (CardsType::Straight, CardsType::Three)
+        }else{
+            (CardsType::Three, CardsType::Straight)
+        };
+        [CardsType::High, CardsType::Pair, CardsType::Pair2, fifth, sixth,
+         CardsType::Full, CardsType::Flush, CardsType::Four, CardsType::StraightFlush]
+    }
+
+    #[inline]
+    fn pack(&self, ctype: CardsType, values: [u8;5]) -> u32 {
+        let order = self.cards_types().iter().position(|&t| t == ctype).unwrap() as u32 + 1;
+        values.iter().fold(order, |acc, &v| (acc << 4) | (v as u32 + 1))
+    }
+}
+
+// 最大的顺子, 返回 5 张牌值 (0-12)
+fn best_straight(mask: u16) -> Option<[u8;5]> {
+    for top in (4..13u8).rev() {
+        let window = 0x1F << (top - 4);
+        if mask & window == window {
+            return Some([top, top-1, top-2, top-3, top-4]);
+        }
+    }
+    if mask & ACE_SIX_STRAIGHT == ACE_SIX_STRAIGHT {
+        return Some([7, 6, 5, 4, 12]);
+    }
+    None
+}
+
+// 从大到小取 5 个牌值, 跳过 excluded, 不够 5 个时后面是 0
+fn top_values(mask: u16, excluded: u16) -> [u8;5] {
+    let mut values = [0;5];
+    let mut n = 0;
+    for v in (0..13u8).rev() {
+        if n < 5 && (mask & !excluded) & (1 << v) != 0 {
+            values[n] = v;
+            n += 1;
+        }
+    }
+    values
+}
+
+impl Evaluator for ShortDeckEvaluator {
+    /// card index with `CardIndexPolicy::GroupByValue`
+    type CardType = u8;
+
+    fn make_card(&self, card: &card::Card) -> u8 {
+        card.card_index(CardIndexPolicy::GroupByValue)
+    }
+
+    /// 5-7 cards without joker, otherwise 0
+    fn eval(&self, cards: &[u8]) -> u32 {
+        // 52 是 joker
+        if cards.len() < 5 || cards.len() > 7 || cards.iter().any(|&c| c >= 52) {
+            return 0;
+        }
+        let mut counts = [0u8;13];
+        let mut suit_masks = [0u16;4];
+        let mut mask = 0u16;
+        for &c in cards.iter() {
+            counts[(c / 4) as usize] += 1;
+            suit_masks[(c % 4) as usize] |= 1 << (c / 4);
+            mask |= 1 << (c / 4);
+        }
+
+        let mut best = 0;
+        for &suit_mask in suit_masks.iter().filter(|m| m.count_ones() >= 5) {
+            if let Some(values) = best_straight(suit_mask) {
+                best = best.max(self.pack(CardsType::StraightFlush, values));
+            }
+            best = best.max(self.pack(CardsType::Flush, top_values(suit_mask, 0)));
+        }
+        if let Some(values) = best_straight(mask) {
+            best = best.max(self.pack(CardsType::Straight, values));
+        }
+
+        // 只看牌值的牌型, 取最大的一个
+        // groups[n]: 恰好 n 张的牌值中最大的两个
+        let mut groups = [[None;2];5];
+        for v in (0..13u8).rev() {
+            let group = &mut groups[counts[v as usize] as usize];
+            if group[0].is_none() {
+                group[0] = Some(v);
+            }else if group[1].is_none() {
+                group[1] = Some(v);
+            }
+        }
+        let (quads, trips, pairs) = (groups[4], groups[3], groups[2]);
+        let ranked = if let Some(q) = quads[0] {
+            let k = top_values(mask, 1 << q);
+            self.pack(CardsType::Four, [q, q, q, q, k[0]])
+        }else if let (Some(t), Some(p)) = (trips[0], trips[1].max(pairs[0])) {
+            // 第二个三条也可以当作对子
+            self.pack(CardsType::Full, [t, t, t, p, p])
+        }else if let Some(t) = trips[0] {
+            let k = top_values(mask, 1 << t);
+            self.pack(CardsType::Three, [t, t, t, k[0], k[1]])
+        }else if let (Some(p1), Some(p2)) = (pairs[0], pairs[1]) {
+            let k = top_values(mask, (1 << p1) | (1 << p2));
+            self.pack(CardsType::Pair2, [p1, p1, p2, p2, k[0]])
+        }else if let Some(p) = pairs[0] {
+            let k = top_values(mask, 1 << p);
+            self.pack(CardsType::Pair, [p, p, k[0], k[1], k[2]])
+        }else{
+            self.pack(CardsType::High, top_values(mask, 0))
+        };
+        best.max(ranked)
+    }
+
+    fn eval_value_type(&self, eval_value: u32) -> Option<CardsType> {
+        match eval_value >> 20 {
+            order @ 1..=9 => Some(self.cards_types()[order as usize - 1]),
+            _ => None,
+        }
+    }
+
+    /// not supported, always None
+    ///
+    /// `HandRank` is in the hold'em order (full house beats flush, A-2-3-4-5 is a straight),
+    /// so `simple_eval_rank` is None too. Use `eval_value_type` and `unpack_eval_value` instead.
+    fn hand_rank(&self, _eval_value: u32) -> Option<HandRank> {
+        None
+    }
+
+    fn unpack_eval_value(&self, eval_value: u32) -> Option<(CardsType, Vec<card::Value>)> {
+        let ctype = self.eval_value_type(eval_value)?;
+        let values = (0..5).rev()
+            .map(|i| card::Value::from_index(((eval_value >> (i * 4)) & 0xF) as u8 - 1))
+            .collect();
+        Some((ctype, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{parse_cards, Card, Value};
+
+    fn eval(evaluator: &ShortDeckEvaluator, s: &str) -> u32 {
+        evaluator.simple_eval(&parse_cards::<Vec<Card>>(s).unwrap())
+    }
+
+    #[test]
+    fn test_short_deck_order(){
+        let evaluator = ShortDeckEvaluator::new();
+        assert!(eval(&evaluator, "As9s8s7sJsKd") > eval(&evaluator, "AsAdAcKsKd9h"));
+        assert!(eval(&evaluator, "AsAdAcKsKd9h") > eval(&evaluator, "AsKdQcJsTd"));
+        assert!(eval(&evaluator, "Ts9d8c7s6d") > eval(&evaluator, "As9d8c7s6d"));
+        assert!(eval(&evaluator, "As9d8c7s6d") > eval(&evaluator, "AsAdAc7s6d"));
+
+        let (ctype, values) = evaluator.unpack_eval_value(eval(&evaluator, "As9d8c7s6dKh")).unwrap();
+        assert_eq!(ctype, CardsType::Straight);
+        assert_eq!(values, vec![Value::Nign, Value::Eight, Value::Seven, Value::Six, Value::Ace]);
+        let (ctype, _) = evaluator.unpack_eval_value(eval(&evaluator, "As9s8s7s6s")).unwrap();
+        assert_eq!(ctype, CardsType::StraightFlush);
+        let (ctype, values) = evaluator.unpack_eval_value(eval(&evaluator, "KsKdKcQsQdQh6s")).unwrap();
+        assert_eq!(ctype, CardsType::Full);
+        assert_eq!(values, vec![Value::Kine, Value::Kine, Value::Kine, Value::Queen, Value::Queen]);
+
+        let evaluator = ShortDeckEvaluator::trips_beat_straight();
+        assert!(eval(&evaluator, "As9d8c7s6d") < eval(&evaluator, "AsAdAc7s6d"));
+        assert_eq!(evaluator.eval_value_type(eval(&evaluator, "AsAdAc7s6d")), Some(CardsType::Three));
+        assert_eq!(evaluator.simple_eval_rank(&parse_cards::<Vec<Card>>("AsAdAc7s6d").unwrap()), None);
+        assert_eq!(eval(&evaluator, "JkAsAdAc7s6d"), 0);
+    }
+}
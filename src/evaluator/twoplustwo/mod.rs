@@ -1,80 +1,216 @@
-use crate::Evaluator;
+use crate::{Evaluator, IncrementalEvaluator};
 use crate::card;
 use crate::cards_type::CardsType;
-use crate::cards_type;
+use crate::hand_rank::HandRank;
+use crate::tools::twoplustwo::{table_checksum, DATA_FILE_CHECKSUM, DATA_FILE_LEN};
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, Read};
+use std::path::Path;
+#[cfg(feature = "embedded")]
+use std::sync::OnceLock;
 
-const VALUE_CONTENTS: [u32;7462] = include!("../cactuskev/tbl_value_contents");
+/// Error of loading the twoplustwo data file
+#[derive(Debug)]
+pub enum DataFileError {
+    Io(io::Error),
+    /// file size in bytes is not `4 * DATA_FILE_LEN`
+    InvalidSize{expected: u64, actual: u64},
+    /// file content is not generated by `generate_data_file`
+    ChecksumMismatch{expected: u64, actual: u64},
+}
+
+impl fmt::Display for DataFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataFileError::Io(e) => write!(f, "failed to read twoplustwo data file: {}", e),
+            DataFileError::InvalidSize{expected, actual} =>
+                write!(f, "invalid twoplustwo data file size {}, expected {}", actual, expected),
+            DataFileError::ChecksumMismatch{expected, actual} =>
+                write!(f, "twoplustwo data file checksum {:016x} mismatch, expected {:016x}", actual, expected),
+        }
+    }
+}
+
+impl Error for DataFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DataFileError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DataFileError {
+    fn from(e: io::Error) -> DataFileError {
+        DataFileError::Io(e)
+    }
+}
+
+enum Table {
+    Owned(Vec<u32>),
+    #[cfg(feature = "embedded")]
+    Static(&'static [u32]),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Table {
+    #[inline]
+    fn as_slice(&self) -> &[u32] {
+        match self {
+            Table::Owned(hr) => hr,
+            #[cfg(feature = "embedded")]
+            Table::Static(hr) => hr,
+            #[cfg(feature = "mmap")]
+            Table::Mapped(mmap) => {
+                // mmap 按页对齐, 且只在小端平台上使用, 所以可以直接当作 u32 读
+                let (prefix, hr, _) = unsafe { mmap.align_to::<u32>() };
+                debug_assert!(prefix.is_empty());
+                hr
+            }
+        }
+    }
+}
+
+fn check_table(hr: &[u32]) -> Result<(), DataFileError> {
+    let checksum = table_checksum(hr);
+    if checksum != DATA_FILE_CHECKSUM {
+        return Err(DataFileError::ChecksumMismatch{expected: DATA_FILE_CHECKSUM, actual: checksum});
+    }
+    Ok(())
+}
+
+fn check_size(file: &File) -> Result<(), DataFileError> {
+    let expected = 4 * DATA_FILE_LEN as u64;
+    let actual = file.metadata()?.len();
+    if actual != expected {
+        return Err(DataFileError::InvalidSize{expected, actual});
+    }
+    Ok(())
+}
+
+// build.rs 生成的表格, 按 4 字节对齐以便直接当作 u32 读
+#[cfg(feature = "embedded")]
+#[repr(C, align(4))]
+struct Aligned<T: ?Sized>(T);
 
+#[cfg(feature = "embedded")]
+static EMBEDDED_TABLE: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(env!("OUT_DIR"), "/TptHandRank.dat")));
+
+/// Evaluator by the twoplustwo lookup table
+///
+/// The data file is 32487834 little-endian u32 entries, generated by
+/// `tools::twoplustwo::generate_data_file`. Size and checksum are verified on load.
 pub struct TwoPlusTwoEvaluator{
-    hr: Vec<u32>,
+    hr: Table,
 }
 
 impl TwoPlusTwoEvaluator {
-    pub fn with_data_file(filename: &str) -> Self{
-        let mut f = File::open(filename).expect("twoplustwo data file not found");
-        let mut hru8: Vec<u8> = Vec::with_capacity(4*32487834);
-        f.read_to_end(&mut hru8).ok().unwrap();
-
-        let ptr = hru8.as_ptr();
-        TwoPlusTwoEvaluator{
-            hr: unsafe {
-                std::mem::forget(hru8);
-                Vec::from_raw_parts(ptr as *mut u32, 32487834, 32487834)
-            },
+    /// load data file into memory
+    ///
+    /// Example:
+    /// ```no_run
+    /// let evaluator = pokerlib::TwoPlusTwoEvaluator::from_file("TptHandRank.dat")
+    ///     .expect("invalid data file");
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TwoPlusTwoEvaluator, DataFileError> {
+        let mut file = File::open(path)?;
+        check_size(&file)?;
+
+        let mut hr: Vec<u32> = Vec::with_capacity(DATA_FILE_LEN);
+        let mut buf = vec![0u8; 1 << 16];
+        while hr.len() < DATA_FILE_LEN {
+            let n = ((DATA_FILE_LEN - hr.len()) * 4).min(buf.len());
+            file.read_exact(&mut buf[..n])?;
+            hr.extend(buf[..n].chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])));
         }
+        check_table(&hr)?;
+        Ok(TwoPlusTwoEvaluator{hr: Table::Owned(hr)})
     }
 
-    #[inline]
-    fn get_at(&self, pos: u32) -> u32 {
-        unsafe {
-            *self.hr.as_ptr().add(pos as usize)
+    /// map data file into memory, processes mapping the same file share the pages
+    ///
+    /// The file must not be modified while mapped. On big-endian platforms this is same as `from_file`.
+    #[cfg(feature = "mmap")]
+    pub fn from_file_mmap<P: AsRef<Path>>(path: P) -> Result<TwoPlusTwoEvaluator, DataFileError> {
+        if cfg!(target_endian = "big") {
+            return TwoPlusTwoEvaluator::from_file(path);
         }
+        let file = File::open(path)?;
+        check_size(&file)?;
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        let evaluator = TwoPlusTwoEvaluator{hr: Table::Mapped(mmap)};
+        check_table(evaluator.hr.as_slice())?;
+        Ok(evaluator)
     }
-    /// 解析一个结果： 包含牌型以及每个牌值（没有花色）
-    pub fn unpack_eval_value(&self, eval_value: u32) -> Option<(CardsType, Vec<card::Value>)>{
-        let ctype = self.eval_value_type(eval_value);
-        if ctype.is_none(){
-            return None;
+
+    /// the evaluator with the table generated at compile time, no data file needed
+    ///
+    /// The table (130M) is embedded in the binary. It's initialized on first call,
+    /// only big-endian platforms need to decode it.
+    ///
+    /// Example:
+    /// ```
+    /// use pokerlib::Evaluator;
+    ///
+    /// let evaluator = pokerlib::TwoPlusTwoEvaluator::embedded();
+    /// let value = evaluator.simple_eval(&pokerlib::Card::one_desk_cards()[0..7]);
+    /// ```
+    #[cfg(feature = "embedded")]
+    pub fn embedded() -> &'static TwoPlusTwoEvaluator {
+        static EVALUATOR: OnceLock<TwoPlusTwoEvaluator> = OnceLock::new();
+        EVALUATOR.get_or_init(|| {
+            let bytes = &EMBEDDED_TABLE.0;
+            let hr = if cfg!(target_endian = "little") {
+                // 已按 4 字节对齐, prefix 和 suffix 都为空
+                let (_, hr, _) = unsafe { bytes.align_to::<u32>() };
+                Table::Static(hr)
+            }else{
+                Table::Owned(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+            };
+            TwoPlusTwoEvaluator{hr}
+        })
+    }
+
+    /// load data file, panic if failed
+    #[deprecated(note = "use `from_file` which returns error instead of panic")]
+    pub fn with_data_file(filename: &str) -> Self{
+        match TwoPlusTwoEvaluator::from_file(filename) {
+            Ok(evaluator) => evaluator,
+            Err(e) => panic!("{}", e),
         }
-        let ctype = ctype.unwrap();
-
-        let mut idx: u16 = (eval_value&0xFFF) as u16;
-        // 表中 从小到大排列的， 而 eval_value 中存的是本类型内从大到小的排列
-        idx = match ctype {
-            CardsType::High => idx,
-            CardsType::Pair =>
-                cards_type::HIGH_RANK_COUNT + idx,
-            CardsType::Pair2 =>
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT
-                + idx,
-            CardsType::Three => 
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT
-                + idx,
-            CardsType::Straight => 
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT + cards_type::THREE_RANK_COUNT
-                + idx,
-            CardsType::Flush =>
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT + cards_type::THREE_RANK_COUNT + cards_type::STRAIGHT_RANK_COUNT 
-                + idx,
-            CardsType::Full => 
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT + cards_type::THREE_RANK_COUNT + cards_type::STRAIGHT_RANK_COUNT + cards_type::FLUSH_RANK_COUNT
-                + idx,
-            CardsType::Four => 
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT + cards_type::THREE_RANK_COUNT + cards_type::STRAIGHT_RANK_COUNT + cards_type::FLUSH_RANK_COUNT + cards_type::FULL_RANK_COUNT 
-                + idx,
-            CardsType::StraightFlush => 
-                cards_type::HIGH_RANK_COUNT + cards_type::PAIR_RANK_COUNT + cards_type::PAIR2_RANK_COUNT + cards_type::THREE_RANK_COUNT + cards_type::STRAIGHT_RANK_COUNT + cards_type::FLUSH_RANK_COUNT + cards_type::FULL_RANK_COUNT + cards_type::FOUR_RANK_COUNT 
-                + idx,
-        } - 1;
-        let value = VALUE_CONTENTS[idx as usize];
-        let mut cards: Vec<card::Value> = Vec::new();
-        for i in 0..5 {
-            let v = (value >> (4-i)*4) & 0xF;
-            cards.push(card::Value::from_index(v as u8 - 1));
+    }
+}
+
+/// state of `TwoPlusTwoEvaluator` as `IncrementalEvaluator`: the table node and cards count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwoPlusTwoState {
+    node: u32,
+    count: u8,
+}
+
+impl IncrementalEvaluator for TwoPlusTwoEvaluator {
+    type State = TwoPlusTwoState;
+
+    #[inline]
+    fn start(&self) -> TwoPlusTwoState {
+        TwoPlusTwoState{node: 53, count: 0}
+    }
+
+    #[inline]
+    fn add_card(&self, state: TwoPlusTwoState, card: u32) -> TwoPlusTwoState {
+        TwoPlusTwoState{node: self.hr.as_slice()[(state.node + card) as usize], count: state.count + 1}
+    }
+
+    #[inline]
+    fn finish(&self, state: TwoPlusTwoState) -> u32 {
+        if state.count < 7 {
+            self.hr.as_slice()[state.node as usize]
+        }else{
+            state.node
         }
-        Some((ctype, cards))
     }
 }
 
@@ -97,17 +233,28 @@ impl Evaluator for TwoPlusTwoEvaluator {
             7 => Some(CardsType::Full),
             8 => Some(CardsType::Four),
             9 => Some(CardsType::StraightFlush),
-            _ => return None,
+            _ => None,
         }
     }
     
+    // hhhhrrrrrrrrrrrr: hhhh = cardstype 1-9, r..r = rank within the cardstype, 1 is the worst
+    fn hand_rank(&self, eval_value: u32) -> Option<HandRank> {
+        let ctype = self.eval_value_type(eval_value)?;
+        let idx = (eval_value & 0xFFF) as u16;
+        if idx == 0 {
+            return None;
+        }
+        HandRank::with_class_index(ctype, idx - 1)
+    }
+
     fn eval(&self, input_cards: &[u32]) -> u32 {
+        let hr = self.hr.as_slice();
         let mut p: u32 = 53;
-        for i in 0..input_cards.len() {
-            p = self.get_at(p + input_cards[i]);
+        for &card in input_cards {
+            p = hr[(p + card) as usize];
         }
         if input_cards.len() < 7 {
-            p = self.get_at(p);
+            p = hr[p as usize];
         }
         p
     }
@@ -0,0 +1,238 @@
+use crate::Evaluator;
+use crate::card::{self, Card, Suit, Value};
+use crate::cards_type::CardsType;
+use crate::hand_rank::HandRank;
+use super::NativeEvaluator;
+
+const SUITS: [Suit;4] = [Suit::Spade, Suit::Heart, Suit::Club, Suit::Diamond];
+
+/// Wild cards evaluator, for jokers and "deuces wild"
+///
+/// Jokers (`Card::JOKER`) are always wild, `with_wild_value` makes every card of a value wild too.
+/// A wild card can be any card, even one already in hand, so five of a kind (`CardsType::Five`)
+/// is possible and beats straight flush. Flushes never pair a value.
+///
+/// eval value: same as `NativeEvaluator`, five of a kind is 10 << 20 | value << 16.
+/// `hand_rank` is None for five of a kind.
+///
+/// Instead of trying 52 cards for every wild card, it checks five of a kind, straight flush
+/// and flush by suit, then tries the value multisets of at most 3 wild cards with a suit that can't
+/// make a flush.
+///
+/// Example:
+/// ```
+/// use pokerlib::Evaluator;
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::cards_type::CardsType;
+/// use pokerlib::evaluator::WildEvaluator;
+///
+/// let evaluator = WildEvaluator::new();
+/// let value = evaluator.simple_eval(&parse_cards::<Vec<_>>("AsAdAhAcJk").unwrap());
+/// assert_eq!(evaluator.eval_value_type(value), Some(CardsType::Five));
+/// ```
+pub struct WildEvaluator {
+    wild_value: Option<Value>,
+    native: NativeEvaluator,
+}
+
+impl Default for WildEvaluator {
+    fn default() -> WildEvaluator {
+        WildEvaluator::new()
+    }
+}
+
+impl WildEvaluator {
+    /// only jokers are wild
+    pub fn new() -> WildEvaluator {
+        WildEvaluator{wild_value: None, native: NativeEvaluator::new()}
+    }
+
+    /// jokers and all cards of the value are wild
+    pub fn with_wild_value(value: Value) -> WildEvaluator {
+        WildEvaluator{wild_value: Some(value), native: NativeEvaluator::new()}
+    }
+
+    /// deuces wild video poker
+    pub fn deuces_wild() -> WildEvaluator {
+        WildEvaluator::with_wild_value(Value::Two)
+    }
+
+    pub fn wild_value(&self) -> Option<Value> {
+        self.wild_value
+    }
+
+    #[inline]
+    pub fn is_wild(&self, card: &Card) -> bool {
+        card.is_joker() || Some(card.value()) == self.wild_value
+    }
+
+    #[inline]
+    fn native_card(&self, value_index: u8, suit: Suit) -> u32 {
+        self.native.make_card(&Card::new(Value::from_index(value_index), suit))
+    }
+
+    // 5 张同花色的牌
+    fn eval_suited(&self, value_mask: u16, suit: Suit) -> u32 {
+        let cards: Vec<u32> = (0..13u8).filter(|&v| value_mask & (1 << v) != 0)
+            .map(|v| self.native_card(v, suit))
+            .collect();
+        self.native.eval(&cards)
+    }
+
+    // 同花顺和同花, 每个花色只用该花色没有的牌值补齐
+    fn best_suited(&self, naturals: &[Card], wilds: u32) -> u32 {
+        let mut best = 0;
+        for &suit in SUITS.iter() {
+            let mask = naturals.iter().filter(|c| c.suit() == suit)
+                .fold(0u16, |acc, c| acc | (1 << c.value_index()));
+            if mask.count_ones() + wilds < 5 {
+                continue;
+            }
+            // A-5 is the last window
+            for top in (3..13u16).rev() {
+                let window = if top == 3 {0x100F} else {0x1F << (top - 4)};
+                if 5 - (mask & window).count_ones() <= wilds {
+                    best = best.max(self.eval_suited(window, suit));
+                    break;
+                }
+            }
+            let mut flush = mask;
+            for v in (0..13).rev() {
+                if flush.count_ones() == mask.count_ones() + wilds {
+                    break;
+                }
+                flush |= 1 << v;
+            }
+            while flush.count_ones() > 5 {
+                flush &= flush - 1;
+            }
+            best = best.max(self.eval_suited(flush, suit));
+        }
+        best
+    }
+
+    // 野牌按牌值的多重集合枚举
+    fn best_substitution(&self, cards: &mut [u32], start: usize, min_value: u8, suit: Suit) -> u32 {
+        if start == cards.len() {
+            return self.native.eval(cards);
+        }
+        (min_value..13).map(|v| {
+            cards[start] = self.native_card(v, suit);
+            self.best_substitution(cards, start + 1, v, suit)
+        }).max().unwrap()
+    }
+}
+
+impl Evaluator for WildEvaluator {
+    /// card format: `Card`, jokers included
+    type CardType = Card;
+
+    fn make_card(&self, card: &Card) -> Card {
+        *card
+    }
+
+    fn eval(&self, cards: &[Card]) -> u32 {
+        if cards.len() > 7 || cards.len() < 5 {
+            return 0;
+        }
+        let naturals: Vec<Card> = cards.iter().filter(|c| !self.is_wild(c)).cloned().collect();
+        let wilds = (cards.len() - naturals.len()) as u32;
+        if wilds == 0 {
+            let cards: Vec<u32> = cards.iter().map(|c| self.native.make_card(c)).collect();
+            return self.native.eval(&cards);
+        }
+
+        // 五条
+        let five = (0..13u8).rev()
+            .find(|&v| naturals.iter().filter(|c| c.value_index() == v).count() as u32 + wilds >= 5);
+        if let Some(v) = five {
+            return (10 << 20) | ((v as u32 + 1) << 16);
+        }
+
+        let suited = self.best_suited(&naturals, wilds);
+        // 在最少的花色上补牌, 不会组成同花
+        let suit = *SUITS.iter()
+            .min_by_key(|&&s| naturals.iter().filter(|c| c.suit() == s).count())
+            .unwrap();
+        let mut inner: Vec<u32> = naturals.iter().map(|c| self.native.make_card(c)).collect();
+        inner.resize(cards.len(), 0);
+        suited.max(self.best_substitution(&mut inner, naturals.len(), 0, suit))
+    }
+
+    fn eval_value_type(&self, eval_value: u32) -> Option<CardsType> {
+        match eval_value >> 20 {
+            10 => Some(CardsType::Five),
+            _ => self.native.eval_value_type(eval_value),
+        }
+    }
+
+    /// None for five of a kind
+    fn hand_rank(&self, eval_value: u32) -> Option<HandRank> {
+        self.native.hand_rank(eval_value)
+    }
+
+    fn unpack_eval_value(&self, eval_value: u32) -> Option<(CardsType, Vec<card::Value>)> {
+        match self.eval_value_type(eval_value)? {
+            CardsType::Five => Some((CardsType::Five,
+                                     vec![Value::from_index(((eval_value >> 16) & 0xF) as u8 - 1); 5])),
+            _ => self.hand_rank(eval_value).map(|rank| (rank.category(), rank.best_values().to_vec())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::rng::{Rng, SeededRng};
+
+    fn unpack(evaluator: &WildEvaluator, s: &str) -> (CardsType, Vec<Value>) {
+        let value = evaluator.simple_eval(&parse_cards::<Vec<Card>>(s).unwrap());
+        evaluator.unpack_eval_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_wild_cards(){
+        let evaluator = WildEvaluator::new();
+        assert_eq!(unpack(&evaluator, "AsAdAhAcJk"), (CardsType::Five, vec![Value::Ace; 5]));
+        assert_eq!(unpack(&evaluator, "7s7dJkJk2c").0, CardsType::Four);
+        assert_eq!(unpack(&evaluator, "AsKsQsTsJk3d").0, CardsType::StraightFlush);
+        assert_eq!(unpack(&evaluator, "9s8s7sJkJk2d").1,
+                   vec![Value::Jack, Value::Ten, Value::Nign, Value::Eight, Value::Seven]);
+        // 同花不能有对子
+        assert_eq!(unpack(&evaluator, "AsKsQs9s3dJk").1,
+                   vec![Value::Ace, Value::Kine, Value::Queen, Value::Jack, Value::Nign]);
+        assert_eq!(unpack(&evaluator, "Jk2s3d4c5hAd"), (CardsType::Straight,
+                   vec![Value::Six, Value::Five, Value::Four, Value::Three, Value::Two]));
+
+        let deuces = WildEvaluator::deuces_wild();
+        assert_eq!(unpack(&deuces, "2s2dKhKcKd").0, CardsType::Five);
+        assert_eq!(unpack(&deuces, "2sAdKhQcTd").0, CardsType::Straight);
+        assert_eq!(evaluator.hand_rank(evaluator.simple_eval(&parse_cards::<Vec<Card>>("AsAdAhAcJk").unwrap())), None);
+    }
+
+    #[test]
+    fn test_one_joker_brute_force(){
+        let evaluator = WildEvaluator::new();
+        let native = NativeEvaluator::new();
+        let all = Card::one_desk_cards();
+        let mut rng = SeededRng::new(20);
+        for _ in 0..300 {
+            let mut hand: Vec<Card> = (0..6).map(|_| all[rng.gen_range(52) as usize]).collect();
+            hand.sort();
+            hand.dedup();
+            let expected = all.iter().filter(|c| !hand.contains(c)).map(|&c| {
+                let mut cards = hand.clone();
+                cards.push(c);
+                native.simple_eval(&cards)
+            }).max().unwrap();
+            let mut cards = hand.clone();
+            cards.push(Card::JOKER);
+            let value = evaluator.simple_eval(&cards);
+            // 只有五条和重复牌值的同花时才会超过不重复的替换
+            if evaluator.eval_value_type(value) != Some(CardsType::Five) {
+                assert_eq!(value, expected, "{:?}", cards);
+            }
+        }
+    }
+}
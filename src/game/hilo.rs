@@ -0,0 +1,176 @@
+use crate::{Evaluator, LowEvaluator};
+use crate::card::Card;
+use crate::evaluator::{AceFiveEvaluator, LowRank};
+use crate::game::omaha::{pair_count, triple_count, OmahaError, OmahaEvaluator, BOARD_TRIPLES, HOLE_PAIRS};
+use crate::hand_rank::HandRank;
+
+/// A player's hand in hi-lo games
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HiLoHand {
+    pub high: HandRank,
+    /// None if no qualifying (eight-or-better) low
+    pub low: Option<LowRank>,
+}
+
+impl HiLoHand {
+    /// Omaha/8: both high and low use exactly 2 hole cards and 3 board cards,
+    /// the two halves may use different cards
+    pub fn omaha<T: Copy, E: Evaluator<CardType=T>>(
+        evaluator: &OmahaEvaluator<E>,
+        hole: &[Card],
+        board: &[Card],
+    ) -> Result<HiLoHand, OmahaError> {
+        let high = evaluator.eval(hole, board)?.rank;
+        let low_evaluator = AceFiveEvaluator::eight_or_better();
+        let hole_cards: Vec<u8> = hole.iter().map(|card| low_evaluator.make_card(card)).collect();
+        let board_cards: Vec<u8> = board.iter().map(|card| low_evaluator.make_card(card)).collect();
+        let triples = &BOARD_TRIPLES[..triple_count(board.len())];
+        let mut low = None;
+        for &(a, b) in HOLE_PAIRS[..pair_count(hole.len())].iter() {
+            for &(x, y, z) in triples.iter() {
+                let five = [hole_cards[a], hole_cards[b], board_cards[x], board_cards[y], board_cards[z]];
+                low = low.max(low_evaluator.eval_low(&five));
+            }
+        }
+        Ok(HiLoHand{high, low})
+    }
+
+    /// Stud/8: best high and best eight-or-better low of any 5 cards,
+    /// None if cards count is not supported or there is a joker
+    pub fn stud<T: Copy, E: Evaluator<CardType=T>>(evaluator: &E, cards: &[Card]) -> Option<HiLoHand> {
+        if cards.iter().any(Card::is_joker) {
+            return None;
+        }
+        let high = evaluator.simple_eval_rank(cards)?;
+        let low = AceFiveEvaluator::eight_or_better().simple_eval_low(cards);
+        Some(HiLoHand{high, low})
+    }
+}
+
+/// Result of splitting a hi-lo pot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiLoSplit {
+    pub high_winners: Vec<usize>,
+    /// empty if no qualifying low, then the high winners scoop
+    pub low_winners: Vec<usize>,
+    /// chips won by every player
+    pub payouts: Vec<u64>,
+}
+
+// 平分 amount, 余下的零头从座位靠前的赢家开始每人一个
+fn split_among(amount: u64, winners: &[usize], payouts: &mut [u64]) {
+    let share = amount / winners.len() as u64;
+    let odd = (amount % winners.len() as u64) as usize;
+    for (i, &winner) in winners.iter().enumerate() {
+        payouts[winner] += share + if i < odd {1} else {0};
+    }
+}
+
+/// Split a pot between the high and the qualifying low hands
+///
+/// `pot` is counted in the smallest chip. `hands` are the players still in the pot,
+/// in seat order starting from the first seat left of the button.
+///
+/// - without qualifying low, the high hands take the whole pot
+/// - otherwise the pot is halved, the odd chip goes to the high half
+/// - each half is split among its winners (quartering when lows tie), odd chips
+///   go one each to the winners closest to the left of the button
+///
+/// Example:
+/// ```
+/// use pokerlib::HandRank;
+/// use pokerlib::game::hilo::{split_pot, HiLoHand};
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::LowEvaluator;
+///
+/// let low = pokerlib::AceFiveEvaluator::eight_or_better()
+///     .simple_eval_low(&parse_cards::<Vec<_>>("As2d3c4h5s").unwrap());
+/// let hands = [
+///     HiLoHand{high: HandRank::MAX, low},
+///     HiLoHand{high: HandRank::MIN, low},
+/// ];
+/// // the first player wins the high and gets a quarter of the low
+/// assert_eq!(split_pot(&hands, 100).payouts, vec![75, 25]);
+/// ```
+pub fn split_pot(hands: &[HiLoHand], pot: u64) -> HiLoSplit {
+    let mut payouts = vec![0; hands.len()];
+    let best_high = match hands.iter().map(|hand| hand.high).max() {
+        Some(high) => high,
+        None => return HiLoSplit{high_winners: vec![], low_winners: vec![], payouts},
+    };
+    let high_winners: Vec<usize> = (0..hands.len()).filter(|&i| hands[i].high == best_high).collect();
+    let best_low = hands.iter().filter_map(|hand| hand.low).max();
+    let low_winners: Vec<usize> = match best_low {
+        Some(low) => (0..hands.len()).filter(|&i| hands[i].low == Some(low)).collect(),
+        None => vec![],
+    };
+
+    if low_winners.is_empty() {
+        split_among(pot, &high_winners, &mut payouts);
+    }else{
+        let low_half = pot / 2;
+        split_among(pot - low_half, &high_winners, &mut payouts);
+        split_among(low_half, &low_winners, &mut payouts);
+    }
+    HiLoSplit{high_winners, low_winners, payouts}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::NativeEvaluator;
+
+    fn cards(s: &str) -> Vec<Card> {
+        parse_cards(s).unwrap()
+    }
+
+    fn hand(high: u16, low: Option<&str>) -> HiLoHand {
+        HiLoHand{
+            high: HandRank::new(high).unwrap(),
+            low: low.and_then(|s| AceFiveEvaluator::eight_or_better().simple_eval_low(&cards(s))),
+        }
+    }
+
+    #[test]
+    fn test_split_pot(){
+        // 无低牌, 高牌平分, 零头给靠前的座位
+        let split = split_pot(&[hand(100, None), hand(100, Some("9s8d7c6h5s")), hand(50, None)], 101);
+        assert!(split.low_winners.is_empty());
+        assert_eq!(split.payouts, vec![51, 50, 0]);
+
+        // 高低各半, 零头给高牌
+        let split = split_pot(&[hand(100, None), hand(50, Some("8s6d4c3hAs"))], 101);
+        assert_eq!(split.payouts, vec![51, 50]);
+
+        // quartering
+        let split = split_pot(&[hand(50, Some("8s6d4c3hAs")), hand(100, Some("8s6d4c3hAs")), hand(10, None)], 100);
+        assert_eq!(split.high_winners, vec![1]);
+        assert_eq!(split.low_winners, vec![0, 1]);
+        assert_eq!(split.payouts, vec![25, 75, 0]);
+
+        let split = split_pot(&[hand(50, Some("7s6d4c3hAs")), hand(100, Some("8s6d4c3hAs")), hand(100, None)], 103);
+        assert_eq!(split.payouts, vec![51, 26, 26]);
+        assert_eq!(split.payouts.iter().sum::<u64>(), 103);
+    }
+
+    #[test]
+    fn test_hi_lo_hands(){
+        let omaha = OmahaEvaluator::new(NativeEvaluator::new());
+        // 公共牌 3 张低牌, 手牌需要另外 2 张不同的低牌
+        let hand = HiLoHand::omaha(&omaha, &cards("AsKd2hKc"), &cards("3c5d8h9sKs")).unwrap();
+        assert_eq!(hand.low.unwrap().values()[0], crate::card::Value::Eight);
+        let hand = HiLoHand::omaha(&omaha, &cards("AsAdKhKc"), &cards("3c5d8h9sKs")).unwrap();
+        assert_eq!(hand.low, None);
+        let hand = HiLoHand::omaha(&omaha, &cards("As2d9h9c"), &cards("3c4d5h9sKs")).unwrap();
+        assert_eq!(hand.low, Some(LowRank::BEST));
+        assert_eq!(hand.high.category(), crate::CardsType::Straight);
+
+        let hand = HiLoHand::stud(&NativeEvaluator::new(), &cards("As2d3h4c5cKsKd")).unwrap();
+        assert_eq!(hand.low, Some(LowRank::BEST));
+        assert_eq!(hand.high.category(), crate::CardsType::Straight);
+
+        assert_eq!(HiLoHand::omaha(&omaha, &cards("JkAsKhKc"), &cards("3c5d8h9sKs")), Err(OmahaError::InvalidCard(Card::JOKER)));
+        assert_eq!(HiLoHand::stud(&NativeEvaluator::new(), &cards("JkAs2d3h4c5cKs")), None);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod hilo;
+pub mod omaha;
+pub mod stud;
+
+pub use omaha::OmahaEvaluator;
@@ -0,0 +1,276 @@
+use crate::Evaluator;
+use crate::card::{Card, Suit};
+use crate::card_set::CardSet;
+use crate::hand_rank::HandRank;
+use std::error::Error;
+use std::fmt;
+
+// 4/5/6 张手牌选 2 张的组合
+pub(crate) const HOLE_PAIRS: [(usize, usize);15] = [
+    (0,1), (0,2), (1,2), (0,3), (1,3), (2,3),
+    (0,4), (1,4), (2,4), (3,4),
+    (0,5), (1,5), (2,5), (3,5), (4,5)];
+
+// 3/4/5 张公共牌选 3 张的组合
+pub(crate) const BOARD_TRIPLES: [(usize, usize, usize);10] = [
+    (0,1,2), (0,1,3), (0,2,3), (1,2,3),
+    (0,1,4), (0,2,4), (1,2,4), (0,3,4), (1,3,4), (2,3,4)];
+
+pub(crate) fn pair_count(n: usize) -> usize {
+    n * (n - 1) / 2
+}
+
+pub(crate) fn triple_count(n: usize) -> usize {
+    n * (n - 1) * (n - 2) / 6
+}
+
+/// Error of Omaha hand evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OmahaError {
+    /// hole cards count should be 4, 5 or 6
+    InvalidHoleCards(usize),
+    /// board cards count should be 3, 4 or 5
+    InvalidBoard(usize),
+    DuplicateCard(Card),
+    /// a joker, Omaha is played with the 52 cards deck
+    InvalidCard(Card),
+    /// the evaluator's 5 cards value can't map to `HandRank`
+    InvalidEvalValue(u32),
+}
+
+impl fmt::Display for OmahaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OmahaError::InvalidHoleCards(n) => write!(f, "invalid hole cards count {}, expected 4-6", n),
+            OmahaError::InvalidBoard(n) => write!(f, "invalid board cards count {}, expected 3-5", n),
+            OmahaError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            OmahaError::InvalidCard(card) => write!(f, "card {} is not in the 52 cards deck", card),
+            OmahaError::InvalidEvalValue(v) => write!(f, "eval value {:#x} has no hand rank", v),
+        }
+    }
+}
+
+impl Error for OmahaError {}
+
+/// The best legal Omaha hand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OmahaHand {
+    /// the backend evaluator's 5 cards eval value, for comparing with the same evaluator
+    pub value: u32,
+    pub rank: HandRank,
+    /// the 2 hole cards used
+    pub hole: [Card;2],
+    /// the 3 board cards used
+    pub board: [Card;3],
+}
+
+impl OmahaHand {
+    /// the 5 cards made this hand
+    pub fn cards(&self) -> [Card;5] {
+        [self.hole[0], self.hole[1], self.board[0], self.board[1], self.board[2]]
+    }
+}
+
+/// Omaha (PLO4/PLO5/PLO6) evaluator: exactly 2 hole cards and 3 board cards
+///
+/// Built on any `Evaluator` backend which evaluates 5 cards.
+/// When no flush is possible, the hand only depends on card values, so
+/// hole pairs and board triples with same values are evaluated once.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardsType, NativeEvaluator};
+/// use pokerlib::game::OmahaEvaluator;
+///
+/// let evaluator = OmahaEvaluator::new(NativeEvaluator::new());
+/// let hole: Vec<Card> = parse_cards("AsKsQdJd").unwrap();
+/// let board: Vec<Card> = parse_cards("2h5h8hTh9h").unwrap();
+/// // no heart in hand, so no flush
+/// let hand = evaluator.eval(&hole, &board).unwrap();
+/// assert_eq!(hand.rank.category(), CardsType::Straight);
+/// ```
+pub struct OmahaEvaluator<E> {
+    evaluator: E,
+}
+
+impl<T: Copy, E: Evaluator<CardType=T>> OmahaEvaluator<E> {
+    pub fn new(evaluator: E) -> OmahaEvaluator<E> {
+        OmahaEvaluator{evaluator}
+    }
+
+    /// the backend evaluator
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    /// eval the best legal hand
+    pub fn eval(&self, hole: &[Card], board: &[Card]) -> Result<OmahaHand, OmahaError> {
+        check_cards(hole, board)?;
+        let (value, pair, triple) = self.best(hole, board);
+        let rank = self.evaluator.hand_rank(value).ok_or(OmahaError::InvalidEvalValue(value))?;
+        Ok(OmahaHand{
+            value,
+            rank,
+            hole: [hole[pair.0], hole[pair.1]],
+            board: [board[triple.0], board[triple.1], board[triple.2]],
+        })
+    }
+
+    /// eval value of the best legal hand, same scale as the backend's 5 cards eval value
+    pub fn eval_value(&self, hole: &[Card], board: &[Card]) -> Result<u32, OmahaError> {
+        check_cards(hole, board)?;
+        Ok(self.best(hole, board).0)
+    }
+
+    // 返回最大值以及所用的手牌组合与公共牌组合
+    fn best(&self, hole: &[Card], board: &[Card]) -> (u32, (usize, usize), (usize, usize, usize)) {
+        let mut hole_cards = [self.evaluator.make_card(&hole[0]);6];
+        for (c, card) in hole_cards.iter_mut().zip(hole.iter()) {
+            *c = self.evaluator.make_card(card);
+        }
+        let mut board_cards = [hole_cards[0];5];
+        for (c, card) in board_cards.iter_mut().zip(board.iter()) {
+            *c = self.evaluator.make_card(card);
+        }
+
+        // 不可能成同花时牌力只和牌值有关, 牌值相同的组合只算一次
+        let dedup = !flush_possible(hole, board);
+        let mut pairs = HOLE_PAIRS;
+        let pair_len = distinct(&HOLE_PAIRS[..pair_count(hole.len())], &mut pairs, dedup,
+                                |(a, b)| value_key(&[hole[a], hole[b]]));
+        let pairs = &pairs[..pair_len];
+        let mut triples = BOARD_TRIPLES;
+        let triple_len = distinct(&BOARD_TRIPLES[..triple_count(board.len())], &mut triples, dedup,
+                                  |(a, b, c)| value_key(&[board[a], board[b], board[c]]));
+        let triples = &triples[..triple_len];
+
+        let mut best = (0, pairs[0], triples[0]);
+        let mut cards = [hole_cards[0];5];
+        let mut first = true;
+        for &pair in pairs.iter() {
+            cards[0] = hole_cards[pair.0];
+            cards[1] = hole_cards[pair.1];
+            for &triple in triples.iter() {
+                cards[2] = board_cards[triple.0];
+                cards[3] = board_cards[triple.1];
+                cards[4] = board_cards[triple.2];
+                let value = self.evaluator.eval(&cards);
+                if first || value > best.0 {
+                    best = (value, pair, triple);
+                    first = false;
+                }
+            }
+        }
+        best
+    }
+}
+
+fn check_cards(hole: &[Card], board: &[Card]) -> Result<(), OmahaError> {
+    if !(4..=6).contains(&hole.len()) {
+        return Err(OmahaError::InvalidHoleCards(hole.len()));
+    }
+    if !(3..=5).contains(&board.len()) {
+        return Err(OmahaError::InvalidBoard(board.len()));
+    }
+    let mut used = CardSet::new();
+    for &card in hole.iter().chain(board.iter()) {
+        if card.is_joker() {
+            return Err(OmahaError::InvalidCard(card));
+        }
+        if !used.insert(card) {
+            return Err(OmahaError::DuplicateCard(card));
+        }
+    }
+    Ok(())
+}
+
+// 某花色公共牌至少 3 张且手牌至少 2 张
+fn flush_possible(hole: &[Card], board: &[Card]) -> bool {
+    let hole: CardSet = hole.iter().collect();
+    let board: CardSet = board.iter().collect();
+    (0..4).map(Suit::from_index)
+        .any(|suit| board.suit_cards(suit).len() >= 3 && hole.suit_cards(suit).len() >= 2)
+}
+
+// 把要算的组合放到 out 前面, 返回个数; dedup 时 key 相同的只留第一个
+fn distinct<C: Copy>(combos: &[C], out: &mut [C], dedup: bool, key: impl Fn(C) -> u64) -> usize {
+    let mut keys = [0u64; HOLE_PAIRS.len()];
+    let mut len = 0;
+    for &combo in combos {
+        if dedup {
+            let k = key(combo);
+            if keys[..len].contains(&k) {
+                continue;
+            }
+            keys[len] = k;
+        }
+        out[len] = combo;
+        len += 1;
+    }
+    len
+}
+
+// 牌值组成的 key, 与顺序无关
+fn value_key(cards: &[Card]) -> u64 {
+    cards.iter().map(|card| 1u64 << (card.value_index() * 4)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::cards_type::CardsType;
+    use crate::deck::Deck;
+    use crate::rng::SeededRng;
+    use crate::NativeEvaluator;
+
+    fn cards(s: &str) -> Vec<Card> {
+        parse_cards(s).unwrap()
+    }
+
+    #[test]
+    fn test_must_use_two(){
+        let evaluator = OmahaEvaluator::new(NativeEvaluator::new());
+        // 公共牌三条 A 加手牌一对 K
+        let hand = evaluator.eval(&cards("KsKdQcJc"), &cards("AhAcAd2s3s")).unwrap();
+        assert_eq!(hand.rank.category(), CardsType::Full);
+        assert_eq!(hand.hole, [cards("Ks")[0], cards("Kd")[0]]);
+
+        // 只有一张红心, 不能成同花
+        let hand = evaluator.eval(&cards("Ah2c3d4s"), &cards("KhQhJh9h8h")).unwrap();
+        assert_eq!(hand.rank.category(), CardsType::High);
+
+        let hand = evaluator.eval(&cards("AhTh2c3d4s5c"), &cards("KhQhJh")).unwrap();
+        assert_eq!(hand.rank, HandRank::MAX);
+
+        assert_eq!(evaluator.eval(&cards("AhTh2c"), &cards("KhQhJh")), Err(OmahaError::InvalidHoleCards(3)));
+        assert_eq!(evaluator.eval(&cards("AhTh2c3c"), &cards("KhQh")), Err(OmahaError::InvalidBoard(2)));
+        assert_eq!(evaluator.eval(&cards("AhTh2c3c"), &cards("KhQhAh")), Err(OmahaError::DuplicateCard(cards("Ah")[0])));
+        assert_eq!(evaluator.eval_value(&cards("JkAsKsQs"), &cards("KhQhJh")), Err(OmahaError::InvalidCard(Card::JOKER)));
+    }
+
+    #[test]
+    fn test_same_as_brute_force(){
+        let native = NativeEvaluator::new();
+        let evaluator = OmahaEvaluator::new(NativeEvaluator::new());
+        let mut rng = SeededRng::new(7);
+        for i in 0..2000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let hole = deck.deal(4 + i % 3).unwrap();
+            let board = deck.deal(3 + i % 3).unwrap();
+            let mut best = 0;
+            for a in 0..hole.len() {
+                for b in a+1..hole.len() {
+                    for &(x, y, z) in BOARD_TRIPLES[..triple_count(board.len())].iter() {
+                        best = best.max(native.simple_eval(&[hole[a], hole[b], board[x], board[y], board[z]]));
+                    }
+                }
+            }
+            let hand = evaluator.eval(&hole, &board).unwrap();
+            assert_eq!(hand.value, best);
+            assert_eq!(native.simple_eval(&hand.cards()), best);
+        }
+    }
+}
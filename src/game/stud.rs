@@ -0,0 +1,231 @@
+use crate::card::{Card, Suit, Value};
+use crate::card_set::CardSet;
+use crate::cards_type::CardsType;
+use std::error::Error;
+use std::fmt;
+
+/// Seven-card stud betting round
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub enum StudStreet {
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+}
+
+impl StudStreet {
+    /// upcards count of every player at this street, the seventh card is dealt down
+    pub fn upcards_count(&self) -> usize {
+        match *self {
+            StudStreet::Third => 1,
+            StudStreet::Fourth => 2,
+            StudStreet::Fifth => 3,
+            StudStreet::Sixth | StudStreet::Seventh => 4,
+        }
+    }
+}
+
+/// Error of stud action order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StudError {
+    NoPlayers,
+    /// upcards count of the player doesn't match the street
+    InvalidUpcards{player: usize, count: usize},
+    DuplicateCard(Card),
+    /// a joker, stud is played with the 52 cards deck
+    InvalidCard(Card),
+}
+
+impl fmt::Display for StudError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StudError::NoPlayers => write!(f, "no players"),
+            StudError::InvalidUpcards{player, count} =>
+                write!(f, "player {} has invalid upcards count {}", player, count),
+            StudError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            StudError::InvalidCard(card) => write!(f, "card {} is not in the 52 cards deck", card),
+        }
+    }
+}
+
+impl Error for StudError {}
+
+// 桥牌花色顺序: 梅花 < 方块 < 红心 < 黑桃
+fn bridge_suit_order(suit: Suit) -> u8 {
+    match suit {
+        Suit::Club => 0,
+        Suit::Diamond => 1,
+        Suit::Heart => 2,
+        Suit::Spade => 3,
+    }
+}
+
+/// Rank of a partial hand of 1-4 upcards, the higher the better
+///
+/// Only pairs, two pair, trips and quads count, straights and flushes don't.
+/// format: cardstype << 16 | 4 card values (4 bit each, Two=1 ... Ace=13, 0 if missing),
+/// values are ordered as `values` returns.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct ShowingRank(u32);
+
+impl ShowingRank {
+    /// None if cards count is not 1-4 or there is a joker
+    pub fn new(cards: &[Card]) -> Option<ShowingRank> {
+        if cards.is_empty() || cards.len() > 4 || cards.iter().any(Card::is_joker) {
+            return None;
+        }
+        let mut counts = [0u8;13];
+        for card in cards.iter() {
+            counts[card.value_index() as usize] += 1;
+        }
+        // 按 (张数, 牌值) 从大到小排列
+        let mut groups: Vec<(u8, u8)> = (0..13u8).rev()
+            .filter(|&v| counts[v as usize] > 0)
+            .map(|v| (counts[v as usize], v))
+            .collect();
+        groups.sort_by_key(|&(count, _)| std::cmp::Reverse(count));
+        let ctype = match (groups[0].0, groups.len()) {
+            (4, _) => CardsType::Four,
+            (3, _) => CardsType::Three,
+            (2, 2) if groups[1].0 == 2 => CardsType::Pair2,
+            (2, _) => CardsType::Pair,
+            _ => CardsType::High,
+        };
+        let nibbles = groups.iter()
+            .flat_map(|&(count, v)| std::iter::repeat(v as u32 + 1).take(count as usize))
+            .chain(std::iter::repeat(0))
+            .take(4)
+            .fold(0, |acc, v| (acc << 4) | v);
+        Some(ShowingRank(((ctype as u32) << 16) | nibbles))
+    }
+
+    /// High, Pair, Pair2, Three or Four
+    pub fn category(&self) -> CardsType {
+        crate::cards_type::ALL_CARDS_TYPES[(self.0 >> 16) as usize]
+    }
+
+    /// card values, ordered by importance, e.g. `[K,K,9]`
+    pub fn values(&self) -> Vec<Value> {
+        (0..4).rev()
+            .map(|i| (self.0 >> (i * 4)) & 0xF)
+            .filter(|&v| v != 0)
+            .map(|v| Value::from_index(v as u8 - 1))
+            .collect()
+    }
+}
+
+fn check_upcards<T: AsRef<[Card]>>(street: StudStreet, upcards: &[T]) -> Result<(), StudError> {
+    if upcards.is_empty() {
+        return Err(StudError::NoPlayers);
+    }
+    let mut used = CardSet::new();
+    for (player, cards) in upcards.iter().enumerate() {
+        let cards = cards.as_ref();
+        if cards.len() != street.upcards_count() {
+            return Err(StudError::InvalidUpcards{player, count: cards.len()});
+        }
+        for &card in cards.iter() {
+            if card.is_joker() {
+                return Err(StudError::InvalidCard(card));
+            }
+            if !used.insert(card) {
+                return Err(StudError::DuplicateCard(card));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The bring-in player of third street: the lowest upcard, ties broken by
+/// suit in bridge order (clubs, diamonds, hearts, spades)
+///
+/// `door_cards` are every player's upcard, None if no players or there is a joker.
+pub fn bring_in(door_cards: &[Card]) -> Option<usize> {
+    if door_cards.iter().any(Card::is_joker) {
+        return None;
+    }
+    (0..door_cards.len())
+        .min_by_key(|&i| (door_cards[i].value(), bridge_suit_order(door_cards[i].suit())))
+}
+
+/// The first player to act at the street
+///
+/// `upcards` are the players still in hand, in seat order starting from the first
+/// seat left of the dealer. Third street starts from the bring-in, later streets
+/// from the best showing hand, ties go to the player closest to the dealer's left.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::Card;
+/// use pokerlib::game::stud::{first_to_act, StudStreet};
+///
+/// let door: Vec<Vec<Card>> = ["Kd", "2c", "2s"].iter().map(|s| parse_cards(s).unwrap()).collect();
+/// assert_eq!(first_to_act(StudStreet::Third, &door), Ok(1));
+///
+/// let upcards: Vec<Vec<Card>> = ["KdQs", "2c2h", "AsJd"].iter().map(|s| parse_cards(s).unwrap()).collect();
+/// assert_eq!(first_to_act(StudStreet::Fourth, &upcards), Ok(1));
+/// ```
+pub fn first_to_act<T: AsRef<[Card]>>(street: StudStreet, upcards: &[T]) -> Result<usize, StudError> {
+    check_upcards(street, upcards)?;
+    if street == StudStreet::Third {
+        let door_cards: Vec<Card> = upcards.iter().map(|cards| cards.as_ref()[0]).collect();
+        return Ok(bring_in(&door_cards).unwrap());
+    }
+    let ranks: Vec<ShowingRank> = upcards.iter()
+        .map(|cards| ShowingRank::new(cards.as_ref()).unwrap())
+        .collect();
+    let best = *ranks.iter().max().unwrap();
+    Ok(ranks.iter().position(|&rank| rank == best).unwrap())
+}
+
+/// Players in acting order at the street: from `first_to_act`, then clockwise
+pub fn action_order<T: AsRef<[Card]>>(street: StudStreet, upcards: &[T]) -> Result<Vec<usize>, StudError> {
+    let first = first_to_act(street, upcards)?;
+    Ok((0..upcards.len()).map(|i| (first + i) % upcards.len()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn upcards(hands: &[&str]) -> Vec<Vec<Card>> {
+        hands.iter().map(|s| parse_cards(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_showing_rank(){
+        let rank = |s: &str| ShowingRank::new(&parse_cards::<Vec<Card>>(s).unwrap()).unwrap();
+        assert!(rank("2c2d") > rank("AsKs"));
+        assert!(rank("2c2d3s3h") > rank("AsAdKcQh"));
+        assert!(rank("9c9d9s") > rank("2c2d3s3h"));
+        assert!(rank("AsKd") > rank("As"));
+        // 同花顺子不算
+        assert!(rank("Ts9s8s7s") < rank("2c2d"));
+        assert_eq!(rank("KsKdKh9c").category(), CardsType::Three);
+        assert_eq!(rank("Ks9dKh").values(), vec![Value::Kine, Value::Kine, Value::Nign]);
+        assert_eq!(ShowingRank::new(&[]), None);
+        assert_eq!(ShowingRank::new(&parse_cards::<Vec<Card>>("JkAs").unwrap()), None);
+    }
+
+    #[test]
+    fn test_action_order(){
+        assert_eq!(first_to_act(StudStreet::Third, &upcards(&["2s", "2h", "2c", "2d", "3c"])), Ok(2));
+        assert_eq!(first_to_act(StudStreet::Third, &upcards(&["Ac", "Kh", "Ks"])), Ok(1));
+
+        // 牌面相同时离庄家左手最近的先行动
+        let order = action_order(StudStreet::Fifth, &upcards(&["Ks8d2c", "AsAd3c", "AhAc3h"])).unwrap();
+        assert_eq!(order, vec![1, 2, 0]);
+        assert_eq!(first_to_act(StudStreet::Sixth, &upcards(&["Ks8d2c4d", "7s7d3c2h", "AhKc3h9s"])), Ok(1));
+
+        assert_eq!(first_to_act(StudStreet::Fourth, &upcards(&["Ks8d", "As"])),
+                   Err(StudError::InvalidUpcards{player: 1, count: 1}));
+        assert_eq!(first_to_act(StudStreet::Third, &upcards(&["Ks", "Ks"])),
+                   Err(StudError::DuplicateCard(parse_cards::<Vec<Card>>("Ks").unwrap()[0])));
+        assert_eq!(first_to_act::<Vec<Card>>(StudStreet::Third, &[]), Err(StudError::NoPlayers));
+        assert_eq!(first_to_act(StudStreet::Third, &upcards(&["Jk", "As"])), Err(StudError::InvalidCard(Card::JOKER)));
+        assert_eq!(bring_in(&parse_cards::<Vec<Card>>("JkAs").unwrap()), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::card::{Card, Suit, Value};
+use crate::deck::Street;
+use crate::tools::combination::CombinationIter;
+
+const SUITS: usize = 4;
+const RANKS: u32 = 13;
+// 每个花色的配置: 每轮的牌数占 4 bit, 第一轮在最高位
+const ROUND_SHIFT: usize = 4;
+const MAX_ROUNDS: usize = 8;
+
+const CANONICAL_SUITS: [Suit;4] = [Suit::Spade, Suit::Heart, Suit::Club, Suit::Diamond];
+
+// C(n, k), 0 if k > n
+fn choose(n: u64, k: u64) -> u64 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1u128, |acc, i| acc * (n - i) as u128 / (i + 1) as u128) as u64
+}
+
+// 多重组合数: 从 n 个中可重复地取 k 个
+#[inline]
+fn multichoose(n: u64, k: u64) -> u64 {
+    choose(n + k - 1, k)
+}
+
+// 牌值集合的 colex 序号
+fn rank_set_index(set: u16) -> u64 {
+    let mut set = set;
+    let mut index = 0;
+    let mut j = 1;
+    while set != 0 {
+        index += choose(set.trailing_zeros() as u64, j);
+        set &= set - 1;
+        j += 1;
+    }
+    index
+}
+
+// rank_set_index 的逆运算, n 个牌值
+fn rank_set_from_index(n: u32, index: u64) -> u16 {
+    let mut index = index;
+    let mut set = 0;
+    for k in (1..=n as u64).rev() {
+        let mut p = k - 1;
+        while choose(p + 1, k) <= index {
+            p += 1;
+        }
+        index -= choose(p, k);
+        set |= 1 << p;
+    }
+    set
+}
+
+// used 中第 n 个未用的牌值
+fn nth_unset(used: u16, n: u32) -> u32 {
+    let mut unset = !used & ((1 << RANKS) - 1);
+    for _ in 0..n {
+        unset &= unset - 1;
+    }
+    unset.trailing_zeros()
+}
+
+// 一轮中的所有规范配置, 降序
+struct RoundTable {
+    configurations: Vec<[u32;SUITS]>,
+    offsets: Vec<u64>,
+    suit_sizes: Vec<[u64;SUITS]>,
+    size: u64,
+}
+
+/// A perfect hand indexer with suit isomorphism
+///
+/// Hands which differ only by a suit permutation get the same index, e.g. AsKs and AhKh.
+/// Cards are dealt in rounds (hole cards, flop, turn, ...), order in a round doesn't matter.
+/// Indexes of a round are dense in `0..size(round)`, `unindex` gives a canonical hand back.
+/// (Kevin Waugh, "A Fast and Optimal Hand Isomorphism Algorithm")
+///
+/// Example:
+/// ```
+/// use pokerlib::HandIndexer;
+/// use pokerlib::card::parse_cards;
+///
+/// let indexer = HandIndexer::hold_em();
+/// assert_eq!(indexer.size(0), 169);
+/// assert_eq!(indexer.size(1), 1_286_792);
+/// let a = indexer.index(&parse_cards::<Vec<_>>("AsKs 2s7d9s").unwrap()).unwrap();
+/// let b = indexer.index(&parse_cards::<Vec<_>>("KhAh 9h2h7c").unwrap()).unwrap();
+/// assert_eq!(a, b);
+/// let hand = indexer.unindex(1, a).unwrap();
+/// assert_eq!(indexer.index(&hand), Some(a));
+/// ```
+pub struct HandIndexer {
+    cards_per_round: Vec<usize>,
+    rounds: Vec<RoundTable>,
+}
+
+impl HandIndexer {
+    /// indexer of the rounds, None if no round, more than 8 rounds or more than 52 cards
+    pub fn new(cards_per_round: &[usize]) -> Option<HandIndexer> {
+        let total: usize = cards_per_round.iter().sum();
+        if cards_per_round.is_empty() || cards_per_round.len() > MAX_ROUNDS || total > 52 {
+            return None;
+        }
+        let mut configurations = vec![vec![]; cards_per_round.len()];
+        enumerate_configurations(cards_per_round, 0, 0, cards_per_round[0] as u32,
+                                 [0;SUITS], [0;SUITS], &mut configurations);
+        let rounds = configurations.into_iter().enumerate()
+            .map(|(round, configurations)| RoundTable::new(cards_per_round.len(), round, configurations))
+            .collect();
+        Some(HandIndexer{cards_per_round: cards_per_round.to_vec(), rounds})
+    }
+
+    /// hold'em: 2 hole cards, flop, turn, river
+    ///
+    /// Turn and river cards are rounds of their own, sizes are 169, 1286792, 55190538 and 2428287420.
+    pub fn hold_em() -> HandIndexer {
+        HandIndexer::new(&[2, 3, 1, 1]).unwrap()
+    }
+
+    /// hold'em at the street: 2 hole cards and the board as one round
+    ///
+    /// Size of the last round is 169, 1286792, 13960050 or 123156254.
+    pub fn hold_em_street(street: Street) -> HandIndexer {
+        match street.board_len() {
+            0 => HandIndexer::new(&[2]).unwrap(),
+            n => HandIndexer::new(&[2, n]).unwrap(),
+        }
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds.len()
+    }
+
+    pub fn cards_per_round(&self) -> &[usize] {
+        &self.cards_per_round
+    }
+
+    /// count of indexes of the round, 0 if no such round
+    pub fn size(&self, round: usize) -> u64 {
+        self.rounds.get(round).map_or(0, |table| table.size)
+    }
+
+    /// index of the hand, the round is by cards count
+    ///
+    /// None if cards count is not the end of a round, or there are duplicate cards or jokers.
+    pub fn index(&self, cards: &[Card]) -> Option<u64> {
+        let round = self.round_of(cards.len())?;
+        let mut used = [0u16;SUITS];
+        let mut suit_index = [0u64;SUITS];
+        let mut suit_multiplier = [1u64;SUITS];
+        let mut configuration = [0u32;SUITS];
+        let mut start = 0;
+        for r in 0..=round {
+            let mut ranks = [0u16;SUITS];
+            let mut shifted_ranks = [0u16;SUITS];
+            for card in &cards[start..start + self.cards_per_round[r]] {
+                if card.is_joker() {
+                    return None;
+                }
+                let suit = card.suit_index() as usize;
+                let bit = 1u16 << card.value_index();
+                if (used[suit] | ranks[suit]) & bit != 0 {
+                    return None;
+                }
+                ranks[suit] |= bit;
+                // 去掉之前轮已用的牌值
+                shifted_ranks[suit] |= bit >> ((bit - 1) & used[suit]).count_ones();
+            }
+            for suit in 0..SUITS {
+                let count = ranks[suit].count_ones();
+                suit_index[suit] += suit_multiplier[suit] * rank_set_index(shifted_ranks[suit]);
+                suit_multiplier[suit] *= choose((RANKS - used[suit].count_ones()) as u64, count as u64);
+                used[suit] |= ranks[suit];
+                configuration[suit] |= count << (ROUND_SHIFT * (self.rounds() - r - 1));
+            }
+            start += self.cards_per_round[r];
+        }
+
+        // 花色按配置降序排列
+        let mut pi = [0, 1, 2, 3];
+        pi.sort_by(|&a, &b| configuration[b].cmp(&configuration[a]));
+        let sorted = [configuration[pi[0]], configuration[pi[1]], configuration[pi[2]], configuration[pi[3]]];
+        let table = &self.rounds[round];
+        let id = table.configurations.binary_search_by(|c| sorted.cmp(c)).ok()?;
+
+        let mut index = table.offsets[id];
+        let mut multiplier = 1;
+        let mut i = 0;
+        while i < SUITS {
+            let j = (i + 1..SUITS).find(|&j| sorted[j] != sorted[i]).unwrap_or(SUITS);
+            let mut group: Vec<u64> = pi[i..j].iter().map(|&s| suit_index[s]).collect();
+            group.sort_unstable();
+            let part: u64 = group.iter().enumerate().map(|(k, &x)| choose(x + k as u64, k as u64 + 1)).sum();
+            index += multiplier * part;
+            multiplier *= multichoose(table.suit_sizes[id][i], (j - i) as u64);
+            i = j;
+        }
+        Some(index)
+    }
+
+    /// canonical hand of the index in the round, cards of all rounds up to it
+    ///
+    /// None if round or index is out of range.
+    pub fn unindex(&self, round: usize, index: u64) -> Option<Vec<Card>> {
+        let table = self.rounds.get(round)?;
+        if index >= table.size {
+            return None;
+        }
+        let id = table.offsets.partition_point(|&offset| offset <= index) - 1;
+        let configuration = table.configurations[id];
+        let mut index = index - table.offsets[id];
+
+        let mut suit_index = [0u64;SUITS];
+        let mut i = 0;
+        while i < SUITS {
+            let j = (i + 1..SUITS).find(|&j| configuration[j] != configuration[i]).unwrap_or(SUITS);
+            let suit_size = table.suit_sizes[id][i];
+            let group_size = multichoose(suit_size, (j - i) as u64);
+            let mut group_index = index % group_size;
+            index /= group_size;
+            // 从大到小解出多重组合中的每个数
+            for (k, si) in suit_index[i..j].iter_mut().enumerate().rev() {
+                let k = k as u64 + 1;
+                let (mut x, mut high) = (0, suit_size);
+                while x + 1 < high {
+                    let mid = (x + high) / 2;
+                    if choose(mid + k - 1, k) <= group_index {
+                        x = mid;
+                    }else{
+                        high = mid;
+                    }
+                }
+                group_index -= choose(x + k - 1, k);
+                *si = x;
+            }
+            i = j;
+        }
+
+        let mut rounds_cards = vec![vec![]; round + 1];
+        for (suit, &(mut si)) in suit_index.iter().enumerate() {
+            let mut used = 0u16;
+            for (r, round_cards) in rounds_cards.iter_mut().enumerate() {
+                let n = (configuration[suit] >> (ROUND_SHIFT * (self.rounds() - r - 1))) & 0xF;
+                let round_size = choose((RANKS - used.count_ones()) as u64, n as u64);
+                let mut shifted = rank_set_from_index(n, si % round_size);
+                si /= round_size;
+                let mut ranks = 0;
+                while shifted != 0 {
+                    let rank = nth_unset(used, shifted.trailing_zeros());
+                    ranks |= 1 << rank;
+                    round_cards.push(Card::new(Value::from_index(rank as u8), CANONICAL_SUITS[suit]));
+                    shifted &= shifted - 1;
+                }
+                used |= ranks;
+            }
+        }
+        Some(rounds_cards.concat())
+    }
+
+    // 牌数正好是某一轮结束时的牌数
+    fn round_of(&self, cards_count: usize) -> Option<usize> {
+        let mut total = 0;
+        for (round, &n) in self.cards_per_round.iter().enumerate() {
+            total += n;
+            if total == cards_count {
+                return Some(round);
+            }
+        }
+        None
+    }
+}
+
+impl RoundTable {
+    fn new(rounds: usize, round: usize, mut configurations: Vec<[u32;SUITS]>) -> RoundTable {
+        configurations.sort_unstable_by(|a, b| b.cmp(a));
+        let mut offsets = Vec::with_capacity(configurations.len());
+        let mut suit_sizes = Vec::with_capacity(configurations.len());
+        let mut size = 0;
+        for configuration in configurations.iter() {
+            let mut sizes = [0u64;SUITS];
+            for (suit, suit_size) in sizes.iter_mut().enumerate() {
+                let mut remaining = RANKS as u64;
+                *suit_size = 1;
+                for r in 0..=round {
+                    let n = ((configuration[suit] >> (ROUND_SHIFT * (rounds - r - 1))) & 0xF) as u64;
+                    *suit_size *= choose(remaining, n);
+                    remaining -= n;
+                }
+            }
+            let mut count = 1;
+            let mut i = 0;
+            while i < SUITS {
+                let j = (i + 1..SUITS).find(|&j| configuration[j] != configuration[i]).unwrap_or(SUITS);
+                count *= multichoose(sizes[i], (j - i) as u64);
+                i = j;
+            }
+            offsets.push(size);
+            suit_sizes.push(sizes);
+            size += count;
+        }
+        RoundTable{configurations, offsets, suit_sizes, size}
+    }
+}
+
+// 枚举每轮的规范配置: 之前都相同的花色, 后面的花色牌数不超过前面的
+fn enumerate_configurations(cards_per_round: &[usize], round: usize, suit: usize, remaining: u32,
+                            configuration: [u32;SUITS], used: [u32;SUITS],
+                            result: &mut Vec<Vec<[u32;SUITS]>>) {
+    let rounds = cards_per_round.len();
+    if suit == SUITS {
+        result[round].push(configuration);
+        if round + 1 < rounds {
+            enumerate_configurations(cards_per_round, round + 1, 0, cards_per_round[round + 1] as u32,
+                                     configuration, used, result);
+        }
+        return;
+    }
+    let shift = ROUND_SHIFT * (rounds - round - 1);
+    let min = if suit == SUITS - 1 {remaining} else {0};
+    let mut max = remaining.min(RANKS - used[suit]);
+    // 前一个花色之前的轮都相同时才受限
+    if suit > 0 && configuration[suit] == configuration[suit - 1] & !(0xF << shift) {
+        max = max.min((configuration[suit - 1] >> shift) & 0xF);
+    }
+    for n in min..=max {
+        let mut next_configuration = configuration;
+        let mut next_used = used;
+        next_configuration[suit] |= n << shift;
+        next_used[suit] += n;
+        enumerate_configurations(cards_per_round, round, suit + 1, remaining - n,
+                                 next_configuration, next_used, result);
+    }
+}
+
+// 同构的 n 张公共牌: 索引器和每个序号的同构数量
+pub(crate) fn canonical_boards(cards: usize) -> (HandIndexer, Vec<u64>) {
+    let indexer = HandIndexer::new(&[cards]).unwrap();
+    let mut weights = vec![0u64; indexer.size(0) as usize];
+    let mut it = CombinationIter::new(52, cards);
+    let mut indexes = vec![0usize; cards];
+    let mut board = vec![Card::JOKER; cards];
+    let all = Card::one_desk_cards();
+    while it.move_next(&mut indexes) {
+        for (card, &i) in board.iter_mut().zip(indexes.iter()) {
+            *card = all[i];
+        }
+        weights[indexer.index(&board).unwrap() as usize] += 1;
+    }
+    (indexer, weights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::deck::Deck;
+    use crate::rng::SeededRng;
+
+    #[test]
+    fn test_hold_em_sizes(){
+        let indexer = HandIndexer::hold_em();
+        assert_eq!((0..4).map(|r| indexer.size(r)).collect::<Vec<u64>>(),
+                   vec![169, 1_286_792, 55_190_538, 2_428_287_420]);
+        assert_eq!([Street::Preflop, Street::Flop, Street::Turn, Street::River].iter()
+                   .map(|&street| {
+                       let indexer = HandIndexer::hold_em_street(street);
+                       indexer.size(indexer.rounds() - 1)
+                   }).collect::<Vec<u64>>(),
+                   vec![169, 1_286_792, 13_960_050, 123_156_254]);
+        assert!(HandIndexer::new(&[]).is_none());
+        assert_eq!(indexer.index(&parse_cards::<Vec<Card>>("AsKsQs").unwrap()), None);
+        assert_eq!(indexer.unindex(0, 169), None);
+    }
+
+    #[test]
+    fn test_index_roundtrip(){
+        let indexer = HandIndexer::new(&[2]).unwrap();
+        for i in 0..169 {
+            assert_eq!(indexer.index(&indexer.unindex(0, i).unwrap()), Some(i));
+        }
+        let indexes: std::collections::HashSet<u64> = (0..1326)
+            .map(|i| indexer.index(&crate::range::combo_from_index(i)).unwrap())
+            .collect();
+        assert_eq!(indexes.len(), 169);
+
+        let indexer = HandIndexer::hold_em();
+        let mut rng = SeededRng::new(21);
+        for _ in 0..2000 {
+            let mut deck = Deck::new();
+            deck.shuffle(&mut rng);
+            let cards = deck.deal(7).unwrap();
+            for (round, n) in [(0, 2), (1, 5), (2, 6), (3, 7)].iter() {
+                let index = indexer.index(&cards[..*n]).unwrap();
+                assert!(index < indexer.size(*round));
+                let canonical = indexer.unindex(*round, index).unwrap();
+                assert_eq!(indexer.index(&canonical), Some(index));
+            }
+            // 同构的手牌: 交换花色
+            let swapped: Vec<Card> = cards.iter()
+                .map(|c| Card::new(c.value(), CANONICAL_SUITS[3 - c.suit_index() as usize]))
+                .collect();
+            assert_eq!(indexer.index(&swapped), indexer.index(&cards));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::card;
+use crate::cards_type::{CardsType, ALL_CARDS_TYPES, FIRST_RANKS, TOTAL_RANK_COUNT};
+
+// 7462 个等价类的牌值，从小到大排列
+//
+// format: cardstype << 20 | 5 个牌值（每个 4 bit, Two=1 ... Ace=13）
+// 同一牌型内按低 20 bit 排序
+static VALUE_CONTENTS: [u32;7462] = include!("evaluator/cactuskev/tbl_value_contents");
+
+/// An evaluator-independent hand rank
+///
+/// Every evaluator's eval value can be mapped onto the 7462 equivalence
+/// classes of five cards: 1 is the worst (7-5-4-3-2), 7462 is the best
+/// (royal straight flush). So ranks from different evaluators can be compared.
+/// Five of a kind (wild cards) is not included, `WildEvaluator` eval values rank it above all.
+///
+/// Example:
+/// ```
+/// use pokerlib::Evaluator;
+/// use pokerlib::CardsType;
+///
+/// let cards = &pokerlib::Card::one_desk_cards()[0..7];
+/// let evaluator = pokerlib::NativeEvaluator::new();
+/// let rank = evaluator.simple_eval_rank(cards).unwrap();
+/// assert_eq!(rank.category(), CardsType::Four);
+/// ```
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct HandRank(u16);
+
+impl HandRank {
+    /// the worst rank
+    pub const MIN: HandRank = HandRank(1);
+    /// the best rank
+    pub const MAX: HandRank = HandRank(TOTAL_RANK_COUNT);
+
+    /// make rank from value in 1..=7462
+    pub fn new(value: u16) -> Option<HandRank> {
+        if (1..=TOTAL_RANK_COUNT).contains(&value) {
+            Some(HandRank(value))
+        }else{
+            None
+        }
+    }
+
+    /// make rank from cardstype and index in this cardstype(0 is the worst)
+    pub fn with_class_index(category: CardsType, class_index: u16) -> Option<HandRank> {
+        if category == CardsType::Five || class_index >= category.rank_count() {
+            return None;
+        }
+        Some(HandRank(category.first_rank() + class_index))
+    }
+
+    /// find rank by cardstype and the 5 major card values
+    ///
+    /// values should be ordered as `best_values` returns.
+    pub fn with_values(category: CardsType, values: &[card::Value]) -> Option<HandRank> {
+        if category == CardsType::Five || values.len() != 5 {
+            return None;
+        }
+        let key = values.iter()
+            .fold(0, |acc, v| (acc << 4) | (v.index() as u32 + 1));
+        let first = category.first_rank() as usize - 1;
+        VALUE_CONTENTS[first..first + category.rank_count() as usize]
+            .binary_search_by_key(&key, |&content| content & 0xFFFFF)
+            .ok()
+            .map(|pos| HandRank((first + pos) as u16 + 1))
+    }
+
+    /// rank value in 1..=7462
+    #[inline]
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+
+    /// the cardstype of this rank
+    pub fn category(&self) -> CardsType {
+        ALL_CARDS_TYPES[self.category_index()]
+    }
+
+    /// index in this rank's cardstype, 0 is the worst
+    pub fn class_index(&self) -> u16 {
+        self.0 - FIRST_RANKS[self.category_index()]
+    }
+
+    #[inline]
+    fn category_index(&self) -> usize {
+        FIRST_RANKS[..ALL_CARDS_TYPES.len()].partition_point(|&first| first <= self.0) - 1
+    }
+
+    /// the 5 major card values
+    ///
+    /// ordered by importance, e.g. full house is `[A,A,A,8,8]`,
+    /// 5-high straight is `[5,4,3,2,A]`.
+    pub fn best_values(&self) -> [card::Value;5] {
+        let content = VALUE_CONTENTS[self.0 as usize - 1];
+        let mut values = [card::Value::Two;5];
+        for (i, v) in values.iter_mut().enumerate() {
+            *v = card::Value::from_index(((content >> ((4-i)*4)) & 0xF) as u8 - 1);
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Value;
+
+    #[test]
+    fn test_rank_category(){
+        assert_eq!(HandRank::MIN.category(), CardsType::High);
+        assert_eq!(HandRank::MAX.category(), CardsType::StraightFlush);
+        assert_eq!(HandRank::new(1278).unwrap().category(), CardsType::Pair);
+        assert_eq!(HandRank::new(1278).unwrap().class_index(), 0);
+        assert_eq!(HandRank::new(0), None);
+        assert_eq!(HandRank::new(7463), None);
+        for t in ALL_CARDS_TYPES.iter() {
+            let first = t.first_rank();
+            assert_eq!(HandRank::new(first).unwrap().category(), *t);
+            assert_eq!(HandRank::new(first + t.rank_count() - 1).unwrap().category(), *t);
+        }
+        assert_eq!(CardsType::Five.first_rank(), TOTAL_RANK_COUNT + 1);
+    }
+
+    #[test]
+    fn test_rank_values(){
+        for value in 1..=TOTAL_RANK_COUNT {
+            let rank = HandRank::new(value).unwrap();
+            assert_eq!(HandRank::with_values(rank.category(), &rank.best_values()), Some(rank));
+            assert_eq!(HandRank::with_class_index(rank.category(), rank.class_index()), Some(rank));
+        }
+        assert_eq!(HandRank::MAX.best_values(),
+                   [Value::Ace, Value::Kine, Value::Queen, Value::Jack, Value::Ten]);
+    }
+}
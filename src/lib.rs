@@ -1,9 +1,29 @@
+pub mod abstraction;
 pub mod card;
+pub mod card_set;
 pub mod cards_type;
+pub mod deck;
+pub mod equity;
+pub mod game;
+pub mod hand_indexer;
+pub mod hand_rank;
+pub mod outs;
+pub mod preflop;
+pub mod range;
+pub mod rng;
+pub mod strength;
 pub mod tools;
 
+#[cfg(test)]
+pub(crate) mod test_util;
+
 pub use card::Card;
+pub use card_set::CardSet;
 pub use cards_type::CardsType;
+pub use deck::Deck;
+pub use hand_indexer::HandIndexer;
+pub use hand_rank::HandRank;
+pub use range::Range;
 
 
 /// An Evaluator trait 
@@ -35,14 +55,127 @@ pub trait Evaluator {
     /// get the cardstype from eval_value.
     fn eval_value_type(&self, eval_value: u32) -> Option<CardsType>;
 
+    /// map eval_value to the evaluator-independent `HandRank`
+    fn hand_rank(&self, eval_value: u32) -> Option<HandRank>;
+
+    /// unpack a eval value
+    ///
+    /// result:
+    ///   - cardstype
+    ///   - result card's values
+    fn unpack_eval_value(&self, eval_value: u32) -> Option<(CardsType, Vec<card::Value>)> {
+        self.hand_rank(eval_value)
+            .map(|rank| (rank.category(), rank.best_values().to_vec()))
+    }
+
+    /// eval many hands in a flat buffer, `cards_per_hand` (5, 6 or 7) cards each
+    ///
+    /// `out[i]` is the eval value of `cards[i*cards_per_hand..(i+1)*cards_per_hand]`.
+    /// Panic if `cards.len()` is not `out.len() * cards_per_hand`.
+    ///
+    /// Example:
+    /// ```
+    /// use pokerlib::Evaluator;
+    ///
+    /// let evaluator = pokerlib::NativeEvaluator::new();
+    /// let cards: Vec<u32> = pokerlib::Card::one_desk_cards()[0..14].iter().map(|c| evaluator.make_card(c)).collect();
+    /// let mut out = [0; 2];
+    /// evaluator.eval_batch(&cards, 7, &mut out);
+    /// assert_eq!(out[1], evaluator.eval(&cards[7..]));
+    /// ```
+    fn eval_batch(&self, cards: &[Self::CardType], cards_per_hand: usize, out: &mut [u32]) {
+        assert!((5..=7).contains(&cards_per_hand), "eval_batch: {} cards per hand", cards_per_hand);
+        assert_eq!(cards.len(), out.len() * cards_per_hand, "eval_batch: cards and out length mismatch");
+        for (hand, value) in cards.chunks_exact(cards_per_hand).zip(out.iter_mut()) {
+            *value = self.eval(hand);
+        }
+    }
+
     /// eval's human interface: use `&[Card]` as argument
     fn simple_eval(&self, cards: &[Card]) -> u32 {
         let inner_cards: Vec<Self::CardType> = cards.iter().map(|x| self.make_card(x)).collect();
         self.eval(&inner_cards)
     }
+
+    /// eval `&[Card]` to `HandRank`, None if cards count is not supported
+    fn simple_eval_rank(&self, cards: &[Card]) -> Option<HandRank> {
+        self.hand_rank(self.simple_eval(cards))
+    }
+}
+
+/// An evaluator which can eval cards one by one
+///
+/// The state after some cards can be cached, e.g. after the hole cards and the flop,
+/// then only the remaining cards are added for every runout.
+/// `finish` gives the same eval value as `eval` of all added cards.
+///
+/// Example:
+/// ```
+/// use pokerlib::{Evaluator, IncrementalEvaluator};
+///
+/// let evaluator = pokerlib::NativeEvaluator::new();
+/// let cards: Vec<u32> = pokerlib::Card::one_desk_cards()[0..7].iter().map(|c| evaluator.make_card(c)).collect();
+/// let flop = cards[..5].iter().fold(evaluator.start(), |state, &c| evaluator.add_card(state, c));
+/// let river = evaluator.add_card(evaluator.add_card(flop, cards[5]), cards[6]);
+/// assert_eq!(evaluator.finish(river), evaluator.eval(&cards));
+/// ```
+pub trait IncrementalEvaluator: Evaluator {
+    /// partial state, cheap to copy
+    type State: Copy;
+
+    /// the state without any card
+    fn start(&self) -> Self::State;
+
+    /// the state after adding a card
+    fn add_card(&self, state: Self::State, card: Self::CardType) -> Self::State;
+
+    /// eval value of the added cards
+    fn finish(&self, state: Self::State) -> u32;
+}
+
+/// A lowball evaluator trait, sibling of `Evaluator`
+///
+/// Lowball games rank hands differently (A-5, 2-7, ...), so every evaluator
+/// has its own rank type. Higher rank is the better (lower) hand.
+///
+/// Example:
+/// ```
+/// use pokerlib::LowEvaluator;
+/// use pokerlib::card::parse_cards;
+///
+/// let evaluator = pokerlib::AceFiveEvaluator::new();
+/// let wheel = evaluator.simple_eval_low(&parse_cards::<Vec<_>>("As2d3c4h5sKsKd").unwrap());
+/// let six = evaluator.simple_eval_low(&parse_cards::<Vec<_>>("As2d3c4h6s").unwrap());
+/// assert!(wheel > six);
+/// ```
+pub trait LowEvaluator {
+    /// evaluator's card type
+    type CardType;
+
+    /// comparable rank, the higher the better
+    type Rank: Ord + Copy;
+
+    /// make evaluator's card from Card
+    fn make_card(&self, card: &Card) -> Self::CardType;
+
+    /// best low rank of the cards
+    ///
+    /// None if cards count is not supported or the hand doesn't qualify.
+    fn eval_low(&self, cards: &[Self::CardType]) -> Option<Self::Rank>;
+
+    /// eval_low's human interface: use `&[Card]` as argument
+    fn simple_eval_low(&self, cards: &[Card]) -> Option<Self::Rank> {
+        let inner_cards: Vec<Self::CardType> = cards.iter().map(|x| self.make_card(x)).collect();
+        self.eval_low(&inner_cards)
+    }
 }
 
 pub mod evaluator;
 pub use evaluator::NativeEvaluator;
 pub use evaluator::CactusKevEvaluator;
 pub use evaluator::TwoPlusTwoEvaluator;
+pub use evaluator::ShortDeckEvaluator;
+pub use evaluator::WildEvaluator;
+pub use evaluator::{AceFiveEvaluator, LowRank};
+pub use evaluator::{DeuceSevenEvaluator, DeuceSevenRank};
+pub use evaluator::{BadugiEvaluator, BadugiRank};
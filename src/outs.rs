@@ -0,0 +1,347 @@
+use crate::Evaluator;
+use crate::card::{Card, Suit, Value};
+use crate::card_set::CardSet;
+use crate::cards_type::CardsType;
+use crate::equity::{EquityError, used_cards};
+use crate::range::Range;
+
+/// A drawing hand on the flop or turn
+///
+/// Only draws using at least one hole card count, and no straight (flush) draws
+/// if it's a straight (flush) already.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Draw {
+    /// four cards of a suit
+    Flush,
+    /// two values make a straight, open-ended or double gutshot
+    OpenEnded,
+    /// one value makes a straight
+    Gutshot,
+    /// three cards of a suit on the flop
+    BackdoorFlush,
+    /// two more values make a straight on the flop
+    BackdoorStraight,
+}
+
+// 所有的顺子, 每个是 13 bit 的牌值 mask, A-5 在最前
+fn straights() -> impl Iterator<Item = u16> {
+    std::iter::once(0x100F).chain((0..9).map(|low| 0x1F << low))
+}
+
+// mask 中新的顺子 (不全是公共牌的)
+#[inline]
+fn has_new_straight(mask: u16, board_mask: u16) -> bool {
+    straights().any(|s| mask & s == s && board_mask & s != s)
+}
+
+/// draws of hole cards on a flop or turn, empty on other boards
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::Card;
+/// use pokerlib::outs::{draws, Draw};
+///
+/// let hole: Vec<Card> = parse_cards("9s8s").unwrap();
+/// let board: Vec<Card> = parse_cards("Ts7d2h").unwrap();
+/// assert_eq!(draws([hole[0], hole[1]], &board), vec![Draw::OpenEnded, Draw::BackdoorFlush]);
+/// ```
+pub fn draws(hole: [Card;2], board: &[Card]) -> Vec<Draw> {
+    if board.len() != 3 && board.len() != 4 {
+        return vec![];
+    }
+    let hole_set: CardSet = hole[..].into();
+    let board_set: CardSet = board.into();
+    let all = hole_set | board_set;
+
+    let mut flush = None;
+    if (0..4).all(|s| all.suit_cards(Suit::from_index(s)).len() < 5) {
+        for suit in (0..4).map(Suit::from_index) {
+            if hole_set.suit_cards(suit).is_empty() {
+                continue;
+            }
+            match all.suit_cards(suit).len() {
+                4 => flush = Some(Draw::Flush),
+                3 if board.len() == 3 && flush.is_none() => flush = Some(Draw::BackdoorFlush),
+                _ => {},
+            }
+        }
+    }
+
+    let (mask, board_mask) = (all.value_mask(), board_set.value_mask());
+    let mut straight = None;
+    if !has_new_straight(mask, board_mask) && straights().all(|s| mask & s != s) {
+        let makers = (0..13).filter(|&v| has_new_straight(mask | 1 << v, board_mask | 1 << v)).count();
+        if makers >= 2 {
+            straight = Some(Draw::OpenEnded);
+        }else if makers == 1 {
+            straight = Some(Draw::Gutshot);
+        }else if board.len() == 3 && (0..13).any(|a| (a+1..13).any(|b| {
+            let runout = (1 << a) | (1 << b);
+            has_new_straight(mask | runout, board_mask | runout)
+        })) {
+            straight = Some(Draw::BackdoorStraight);
+        }
+    }
+
+    // 强的听牌在前
+    let mut result = vec![];
+    result.extend(flush.filter(|&d| d == Draw::Flush));
+    result.extend(straight.filter(|&d| d != Draw::BackdoorStraight));
+    result.extend(flush.filter(|&d| d == Draw::BackdoorFlush));
+    result.extend(straight.filter(|&d| d == Draw::BackdoorStraight));
+    result
+}
+
+/// Outs of hole cards on a flop or turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutsReport {
+    /// cards type now
+    pub current: CardsType,
+    /// every card left in the deck and the cards type with it
+    pub next: Vec<(Card, CardsType)>,
+    /// cards improving only the board: the board with it makes the same cards type
+    pub board_only: CardSet,
+    /// draws, strong first
+    pub draws: Vec<Draw>,
+}
+
+impl OutsReport {
+    /// cards giving a better cards type, without `board_only`
+    pub fn improving(&self) -> CardSet {
+        self.next.iter().filter(|&&(_, t)| t > self.current).map(|&(c, _)| c).collect::<CardSet>() - self.board_only
+    }
+
+    /// cards giving the cards type, if it's better than now, without `board_only`
+    pub fn improving_to(&self, cards_type: CardsType) -> CardSet {
+        if cards_type <= self.current {
+            return CardSet::new();
+        }
+        self.next.iter().filter(|&&(_, t)| t == cards_type).map(|&(c, _)| c).collect::<CardSet>() - self.board_only
+    }
+}
+
+// 只有公共牌 (最多 5 张) 的牌型, 不到 5 张时没有顺子和同花
+fn board_type(board: CardSet) -> CardsType {
+    // groups[n]: 恰好 n 张的牌值个数
+    let mut groups = [0;5];
+    for value in (0..13).map(Value::from_index) {
+        groups[board.value_cards(value).len()] += 1;
+    }
+    let flush = (0..4).any(|s| board.suit_cards(Suit::from_index(s)).len() >= 5);
+    let straight = straights().any(|s| board.value_mask() & s == s);
+    if straight && flush {
+        CardsType::StraightFlush
+    }else if groups[4] > 0 {
+        CardsType::Four
+    }else if groups[3] > 0 && groups[2] > 0 {
+        CardsType::Full
+    }else if flush {
+        CardsType::Flush
+    }else if straight {
+        CardsType::Straight
+    }else if groups[3] > 0 {
+        CardsType::Three
+    }else if groups[2] >= 2 {
+        CardsType::Pair2
+    }else if groups[2] == 1 {
+        CardsType::Pair
+    }else{
+        CardsType::High
+    }
+}
+
+// 检查公共牌和重复的牌, 返回用掉的牌
+fn check_cards(hole: [Card;2], board: &[Card], dead: CardSet) -> Result<CardSet, EquityError> {
+    if board.len() != 3 && board.len() != 4 {
+        return Err(EquityError::InvalidBoard(board.len()));
+    }
+    used_cards(hole.iter().chain(board.iter()), dead)
+}
+
+/// outs report of hole cards on a flop or turn
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, CardsType, NativeEvaluator};
+/// use pokerlib::outs::{outs, Draw};
+///
+/// let hole: Vec<Card> = parse_cards("AsKs").unwrap();
+/// let board: Vec<Card> = parse_cards("Qs7s2d").unwrap();
+/// let report = outs(&NativeEvaluator::new(), [hole[0], hole[1]], &board, CardSet::new()).unwrap();
+/// assert_eq!(report.current, CardsType::High);
+/// assert_eq!(report.improving_to(CardsType::Flush).len(), 9);
+/// // A and K, the cards pairing the board are board only
+/// assert_eq!(report.improving_to(CardsType::Pair).len(), 6);
+/// assert_eq!(report.board_only.len(), 8);
+/// assert_eq!(report.draws, vec![Draw::Flush, Draw::BackdoorStraight]);
+/// ```
+pub fn outs<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hole: [Card;2],
+    board: &[Card],
+    dead: CardSet,
+) -> Result<OutsReport, EquityError> {
+    let used = check_cards(hole, board, dead)?;
+    let mut cards: Vec<T> = hole.iter().chain(board.iter()).map(|c| evaluator.make_card(c)).collect();
+    let cards_type = |value: u32| evaluator.eval_value_type(value).expect("evaluator should give cards type");
+    let current = cards_type(evaluator.eval(&cards));
+    cards.push(cards[0]);
+    let board_set: CardSet = board.into();
+    let mut board_only = CardSet::new();
+    let next = (!used).iter().map(|card| {
+        *cards.last_mut().unwrap() = evaluator.make_card(&card);
+        let next = cards_type(evaluator.eval(&cards));
+        if next > current && next == board_type(board_set | card.into()) {
+            board_only.insert(card);
+        }
+        (card, next)
+    }).collect();
+    Ok(OutsReport{current, next, board_only, draws: draws(hole, board)})
+}
+
+/// share of the range beaten (ties count half) after every card left in the deck
+///
+/// The opponent's combos with the card are excluded, cards with no combo left are skipped.
+/// For a single opponent hand (e.g. range "AhKh"), the share is 1 on the cards giving the lead.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, NativeEvaluator};
+/// use pokerlib::outs::lead_outs;
+///
+/// let hole: Vec<Card> = parse_cards("AsKs").unwrap();
+/// let board: Vec<Card> = parse_cards("Qs7s2d").unwrap();
+/// let leads = lead_outs(&NativeEvaluator::new(), [hole[0], hole[1]], &board, &"QhQd".parse().unwrap(), CardSet::new()).unwrap();
+/// // 剩下的 8 张黑桃
+/// assert_eq!(leads.iter().filter(|&&(_, share)| share == 1.0).count(), 8);
+/// ```
+pub fn lead_outs<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hole: [Card;2],
+    board: &[Card],
+    range: &Range,
+    dead: CardSet,
+) -> Result<Vec<(Card, f64)>, EquityError> {
+    let used = check_cards(hole, board, dead)?;
+    let opponents: Vec<([Card;2], f64)> = range.combos_without(used)
+        .filter(|&(_, weight)| weight > 0.0)
+        .map(|(combo, weight)| (combo, weight as f64))
+        .collect();
+    if opponents.is_empty() {
+        return Err(EquityError::EmptyRange(0));
+    }
+    let mut ours: Vec<T> = hole.iter().chain(board.iter()).map(|c| evaluator.make_card(c)).collect();
+    ours.push(ours[0]);
+    let mut theirs = ours.clone();
+    let last = ours.len() - 1;
+
+    let mut result = vec![];
+    for card in (!used).iter() {
+        ours[last] = evaluator.make_card(&card);
+        theirs[last] = ours[last];
+        let value = evaluator.eval(&ours);
+        let (mut beaten, mut total) = (0.0, 0.0);
+        for &(combo, weight) in opponents.iter() {
+            if combo.contains(&card) {
+                continue;
+            }
+            theirs[0] = evaluator.make_card(&combo[0]);
+            theirs[1] = evaluator.make_card(&combo[1]);
+            let other = evaluator.eval(&theirs);
+            if value > other {
+                beaten += weight;
+            }else if value == other {
+                beaten += weight / 2.0;
+            }
+            total += weight;
+        }
+        if total > 0.0 {
+            result.push((card, beaten / total));
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NativeEvaluator;
+    use crate::card::parse_cards;
+    use crate::test_util::hand;
+
+    #[test]
+    fn test_draws(){
+        let cases: &[(&str, &str, &[Draw])] = &[
+            ("9s8s", "Ts7d2h", &[Draw::OpenEnded, Draw::BackdoorFlush]),
+            ("9s8s", "Ts7d2s", &[Draw::Flush, Draw::OpenEnded]),
+            ("9s8d", "Ks4d2c", &[]),
+            ("9s8d", "Js5d2c", &[Draw::BackdoorStraight]),
+            ("9s8d", "Js7d2c", &[Draw::Gutshot]),
+            // 双卡顺
+            ("9s7d", "Js5d2cKh", &[]),
+            ("9s7d", "Js5d2c3h", &[]),
+            ("9s7d", "Js5dTc3h", &[Draw::Gutshot]),
+            ("9s5d", "7d8cJh2h", &[Draw::OpenEnded]),
+            // A-5 听牌和后门顺
+            ("As2d", "3c4h9d", &[Draw::Gutshot]),
+            ("Qh2d", "Jc9hKh", &[Draw::Gutshot, Draw::BackdoorFlush]),
+            ("6h2d", "7c8sKd", &[Draw::BackdoorStraight]),
+            // 公共牌的听牌不算
+            ("2h3d", "9sTsJsQs", &[]),
+            ("AsKs", "Qs7s2dJh", &[Draw::Flush, Draw::Gutshot]),
+            // 已经是顺子/同花
+            ("9s8d", "Ts7d6c", &[]),
+            ("2s8s", "TsJs6s", &[Draw::BackdoorStraight]),
+            ("9s8d", "Ts7d6c2h3h", &[]),
+        ];
+        for &(hole, board, expected) in cases {
+            let board: Vec<Card> = parse_cards(board).unwrap();
+            assert_eq!(draws(hand(hole), &board), expected, "{} {:?}", hole, board);
+        }
+    }
+
+    #[test]
+    fn test_outs(){
+        let evaluator = NativeEvaluator::new();
+        let board: Vec<Card> = parse_cards("Ts7d2sKh").unwrap();
+        let report = outs(&evaluator, hand("9s8s"), &board, CardSet::new()).unwrap();
+        assert_eq!(report.next.len(), 46);
+        assert_eq!(report.improving_to(CardsType::Flush).len(), 9);
+        assert_eq!(report.improving_to(CardsType::Straight).len(), 6);
+        assert_eq!(report.improving_to(CardsType::StraightFlush).len(), 0);
+        assert_eq!(report.improving_to(CardsType::High).len(), 0);
+        // 公共牌成对的 10 张不算
+        assert_eq!(report.improving_to(CardsType::Pair).len(), 6);
+        assert_eq!(report.board_only.len(), 10);
+        assert!(report.board_only.iter().all(|c| [Value::Ten, Value::Seven, Value::Two, Value::Kine].contains(&c.value())));
+        assert_eq!(report.improving().len(), 9 + 6 + 6);
+        // 公共牌的三条和两对也不算
+        let report = outs(&evaluator, hand("9s8s"), &parse_cards::<Vec<Card>>("2h2d5c").unwrap(), CardSet::new()).unwrap();
+        assert_eq!(report.current, CardsType::Pair);
+        assert!(report.improving_to(CardsType::Three).is_empty());
+        assert_eq!(report.improving_to(CardsType::Pair2).len(), 6);
+        assert_eq!(report.board_only.len(), 5);
+
+        // 对一手牌领先的牌和逐张比较一致
+        let leads = lead_outs(&evaluator, hand("9s8s"), &board, &"AhAd".parse().unwrap(), CardSet::new()).unwrap();
+        assert_eq!(leads.len(), 44);
+        for &(card, share) in leads.iter() {
+            let mut ours: Vec<Card> = parse_cards("9s8sTs7d2sKh").unwrap();
+            let mut theirs: Vec<Card> = parse_cards("AhAdTs7d2sKh").unwrap();
+            ours.push(card);
+            theirs.push(card);
+            let (value, other) = (evaluator.simple_eval(&ours), evaluator.simple_eval(&theirs));
+            assert_eq!(share, if value > other {1.0} else if value == other {0.5} else {0.0}, "{}", card);
+        }
+        let leads = lead_outs(&evaluator, hand("9s8s"), &board, &"AA,KK".parse().unwrap(), CardSet::new()).unwrap();
+        assert_eq!(leads.len(), 46);
+        assert_eq!(outs(&evaluator, hand("9s8s"), &board[..2], CardSet::new()), Err(EquityError::InvalidBoard(2)));
+        assert_eq!(lead_outs(&evaluator, hand("9s8s"), &board[..3], &"9s9d".parse().unwrap(), CardSet::new()),
+                   Err(EquityError::EmptyRange(0)));
+        assert_eq!(outs(&evaluator, hand("9sJk"), &board, CardSet::new()), Err(EquityError::InvalidCard(Card::JOKER)));
+        assert_eq!(outs(&evaluator, hand("9s8s"), &board, "8s".parse().unwrap()), Err(EquityError::DuplicateCard("8s".parse().unwrap())));
+    }
+}
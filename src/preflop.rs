@@ -0,0 +1,136 @@
+use crate::card::Card;
+use crate::range::{HandClass, HAND_CLASS_COUNT};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+/// how many u16 entries in the preflop data file
+pub const PREFLOP_DATA_FILE_LEN: usize = HAND_CLASS_COUNT * HAND_CLASS_COUNT;
+
+/// Error of loading the preflop data file
+#[derive(Debug)]
+pub enum PreflopTableError {
+    Io(io::Error),
+    /// file size in bytes is not `2 * PREFLOP_DATA_FILE_LEN`
+    InvalidSize{expected: u64, actual: u64},
+}
+
+impl fmt::Display for PreflopTableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PreflopTableError::Io(e) => write!(f, "failed to read preflop data file: {}", e),
+            PreflopTableError::InvalidSize{expected, actual} =>
+                write!(f, "invalid preflop data file size {}, expected {}", actual, expected),
+        }
+    }
+}
+
+impl Error for PreflopTableError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PreflopTableError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PreflopTableError {
+    fn from(e: io::Error) -> PreflopTableError {
+        PreflopTableError::Io(e)
+    }
+}
+
+/// Heads-up preflop all-in equities of the 169 starting hand classes
+///
+/// Entry `[hero][villain]` is the hero's equity (win + tie/2), averaged over all combos
+/// of the two classes without shared cards. Classes are indexed by `HandClass::index`.
+///
+/// The data file is the 169x169 entries as little-endian u16, equity scaled by 65535,
+/// generated by `tools::preflop::generate_data_file`.
+///
+/// Example:
+/// ```no_run
+/// use pokerlib::preflop::PreflopTable;
+///
+/// let table = PreflopTable::from_file("PreflopEquity.dat").unwrap();
+/// let equity = table.class_equity("AKo".parse().unwrap(), "QQ".parse().unwrap());
+/// ```
+pub struct PreflopTable {
+    equities: Vec<u16>,
+}
+
+impl PreflopTable {
+    /// make table from 169x169 equities in 0..=1, None if count is wrong
+    pub fn from_equities(equities: &[f64]) -> Option<PreflopTable> {
+        if equities.len() != PREFLOP_DATA_FILE_LEN {
+            return None;
+        }
+        Some(PreflopTable{
+            equities: equities.iter().map(|&e| (e.clamp(0.0, 1.0) * 65535.0).round() as u16).collect(),
+        })
+    }
+
+    /// load data file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<PreflopTable, PreflopTableError> {
+        let mut file = File::open(path)?;
+        let expected = 2 * PREFLOP_DATA_FILE_LEN as u64;
+        let actual = file.metadata()?.len();
+        if actual != expected {
+            return Err(PreflopTableError::InvalidSize{expected, actual});
+        }
+        let mut buf = Vec::with_capacity(expected as usize);
+        file.read_to_end(&mut buf)?;
+        Ok(PreflopTable{
+            equities: buf.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+        })
+    }
+
+    /// write data file
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        for entry in self.equities.iter() {
+            file.write_all(&entry.to_le_bytes())?;
+        }
+        file.flush()
+    }
+
+    /// hero class's equity against villain class
+    #[inline]
+    pub fn class_equity(&self, hero: HandClass, villain: HandClass) -> f64 {
+        self.equities[hero.index() * HAND_CLASS_COUNT + villain.index()] as f64 / 65535.0
+    }
+
+    /// equity by the classes of the hole cards
+    ///
+    /// It's the class average, e.g. AhKh vs QhQd is same as AKs vs QQ.
+    /// Use `equity::exact_equity` for the exact suits. None if there is a joker.
+    pub fn equity(&self, hero: [Card;2], villain: [Card;2]) -> Option<f64> {
+        Some(self.class_equity(HandClass::of(hero)?, HandClass::of(villain)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preflop_table_file(){
+        let equities: Vec<f64> = (0..PREFLOP_DATA_FILE_LEN)
+            .map(|i| if i / HAND_CLASS_COUNT == i % HAND_CLASS_COUNT {0.5} else {(i % 1000) as f64 / 1000.0})
+            .collect();
+        let table = PreflopTable::from_equities(&equities).unwrap();
+        let path = std::env::temp_dir().join("pokerlib_preflop_equity.dat");
+        table.write_file(&path).unwrap();
+        let loaded = PreflopTable::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let (aa, kk): (HandClass, HandClass) = ("AA".parse().unwrap(), "KK".parse().unwrap());
+        let expected = equities[aa.index() * HAND_CLASS_COUNT + kk.index()];
+        assert!((loaded.class_equity(aa, kk) - expected).abs() < 1e-4);
+        assert_eq!(loaded.equity(aa.combos()[0], kk.combos()[3]), Some(loaded.class_equity(aa, kk)));
+        assert_eq!(loaded.equity([Card::JOKER, aa.combos()[0][0]], kk.combos()[3]), None);
+        assert!(matches!(PreflopTable::from_file("not_exists.dat"), Err(PreflopTableError::Io(_))));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::card::{parse_cards, Card, CardIndexPolicy, Value};
+use crate::card_set::CardSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// count of hold'em starting hand combos: C(52,2)
+pub const COMBO_COUNT: usize = 1326;
+
+/// count of starting hand classes: 13 pairs, 78 suited, 78 offsuit
+pub const HAND_CLASS_COUNT: usize = 169;
+
+/// index of a two cards combo, in 0..1326
+pub fn combo_index(combo: [Card;2]) -> usize {
+    let a = combo[0].card_index(CardIndexPolicy::GroupByValue) as usize;
+    let b = combo[1].card_index(CardIndexPolicy::GroupByValue) as usize;
+    let (low, high) = if a < b {(a, b)} else {(b, a)};
+    high * (high - 1) / 2 + low
+}
+
+/// combo of the index, higher card first
+pub fn combo_from_index(index: usize) -> [Card;2] {
+    let mut high = 1;
+    while (high + 1) * high / 2 <= index {
+        high += 1;
+    }
+    let low = index - high * (high - 1) / 2;
+    [Card::with_card_index(high as u8, CardIndexPolicy::GroupByValue),
+     Card::with_card_index(low as u8, CardIndexPolicy::GroupByValue)]
+}
+
+/// Kind of starting hand class
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum HandClassKind {
+    Pair,
+    Suited,
+    Offsuit,
+}
+
+/// One of the 169 starting hand classes, e.g. AA, AKs, 72o
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct HandClass {
+    high: Value,
+    low: Value,
+    kind: HandClassKind,
+}
+
+impl HandClass {
+    /// make class, values can be in any order. None if pair is not `HandClassKind::Pair`.
+    pub fn new(a: Value, b: Value, kind: HandClassKind) -> Option<HandClass> {
+        let (high, low) = if a >= b {(a, b)} else {(b, a)};
+        if (high == low) != (kind == HandClassKind::Pair) {
+            return None;
+        }
+        Some(HandClass{high, low, kind})
+    }
+
+    /// class of a combo, None if there is a joker
+    pub fn of(combo: [Card;2]) -> Option<HandClass> {
+        if combo[0].is_joker() || combo[1].is_joker() {
+            return None;
+        }
+        let kind = if combo[0].value() == combo[1].value() {
+            HandClassKind::Pair
+        }else if combo[0].suit() == combo[1].suit() {
+            HandClassKind::Suited
+        }else{
+            HandClassKind::Offsuit
+        };
+        HandClass::new(combo[0].value(), combo[1].value(), kind)
+    }
+
+    /// index in 13x13 grid: `row*13 + col`, row and col are value indexes.
+    ///
+    /// pairs are on the diagonal, suited are `row > col`, offsuit are `row < col`.
+    pub fn index(&self) -> usize {
+        let (high, low) = (self.high.index() as usize, self.low.index() as usize);
+        match self.kind {
+            HandClassKind::Pair | HandClassKind::Suited => high * 13 + low,
+            HandClassKind::Offsuit => low * 13 + high,
+        }
+    }
+
+    pub fn from_index(index: usize) -> HandClass {
+        let (row, col) = ((index / 13) as u8, (index % 13) as u8);
+        let kind = if row == col {
+            HandClassKind::Pair
+        }else if row > col {
+            HandClassKind::Suited
+        }else{
+            HandClassKind::Offsuit
+        };
+        HandClass::new(Value::from_index(row), Value::from_index(col), kind).unwrap()
+    }
+
+    pub fn high(&self) -> Value {
+        self.high
+    }
+
+    pub fn low(&self) -> Value {
+        self.low
+    }
+
+    pub fn kind(&self) -> HandClassKind {
+        self.kind
+    }
+
+    /// all combos of this class: 6 for pair, 4 for suited, 12 for offsuit
+    pub fn combos(&self) -> Vec<[Card;2]> {
+        let mut combos = Vec::with_capacity(12);
+        for s1 in 0..4 {
+            for s2 in 0..4 {
+                let ok = match self.kind {
+                    HandClassKind::Pair => s1 < s2,
+                    HandClassKind::Suited => s1 == s2,
+                    HandClassKind::Offsuit => s1 != s2,
+                };
+                if ok {
+                    combos.push([Card::with_index(self.high.index(), s1), Card::with_index(self.low.index(), s2)]);
+                }
+            }
+        }
+        combos
+    }
+
+    /// count of combos of this class
+    pub fn combo_count(&self) -> usize {
+        match self.kind {
+            HandClassKind::Pair => 6,
+            HandClassKind::Suited => 4,
+            HandClassKind::Offsuit => 12,
+        }
+    }
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.high, self.low, match self.kind {
+            HandClassKind::Pair => "",
+            HandClassKind::Suited => "s",
+            HandClassKind::Offsuit => "o",
+        })
+    }
+}
+
+impl FromStr for HandClass {
+    type Err = RangeParseError;
+
+    fn from_str(s: &str) -> Result<HandClass, RangeParseError> {
+        match parse_class(s.trim()) {
+            Some((high, low, Some(kind))) => Ok(HandClass{high, low, kind}),
+            Some((high, low, None)) if high == low => Ok(HandClass{high, low, kind: HandClassKind::Pair}),
+            _ => Err(RangeParseError::InvalidEntry{entry: s.to_string(), position: 0}),
+        }
+    }
+}
+
+/// Error of parsing range notation
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum RangeParseError {
+    /// entry cannot be understood, position is the entry's char position in input
+    InvalidEntry{entry: String, position: usize},
+    /// weight is not a number in 0..=100%
+    InvalidWeight{entry: String, position: usize},
+}
+
+impl fmt::Display for RangeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RangeParseError::InvalidEntry{entry, position} =>
+                write!(f, "invalid range entry '{}' at position {}", entry, position),
+            RangeParseError::InvalidWeight{entry, position} =>
+                write!(f, "invalid weight in range entry '{}' at position {}", entry, position),
+        }
+    }
+}
+
+impl Error for RangeParseError {}
+
+/// A weighted range of hold'em starting hands
+///
+/// Every of the 1326 combos has a weight in `0.0..=1.0`, 0 means not in range.
+///
+/// Notation is comma separated entries, an entry can be:
+///
+/// | entry | meaning |
+/// |-------|---------|
+/// | `TT`, `AKs`, `AKo`, `AK` | a class, `AK` is both suited and offsuit |
+/// | `TT+`, `A5s+`, `KTo+` | pairs up to AA, or kickers up to the one below the high card |
+/// | `TT-77`, `A5s-A2s` | pairs or kickers between |
+/// | `AhKh` | a specific combo |
+///
+/// Any entry can have a weight suffix: `@50%` (or `@50`) in percent, or `:0.5` in fraction.
+///
+/// Example:
+/// ```
+/// use pokerlib::Range;
+///
+/// let range: Range = "TT+, AKs, A5s-A2s, KQo, 65s@50%".parse().unwrap();
+/// assert_eq!(range.combo_count(), 5.0*6.0 + 4.0 + 4.0*4.0 + 12.0 + 2.0);
+/// assert_eq!(range.to_string(), "TT+, AKs, A5s-A2s, KQo, 65s@50%");
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Range {
+    weights: Vec<f32>,
+}
+
+impl Default for Range {
+    fn default() -> Self {
+        Range::new()
+    }
+}
+
+impl fmt::Debug for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Range({})", self)
+    }
+}
+
+impl Range {
+    /// an empty range
+    pub fn new() -> Range {
+        Range{weights: vec![0.0; COMBO_COUNT]}
+    }
+
+    /// all 1326 combos
+    pub fn full() -> Range {
+        Range{weights: vec![1.0; COMBO_COUNT]}
+    }
+
+    /// weight of a combo
+    pub fn weight(&self, combo: [Card;2]) -> f32 {
+        self.weights[combo_index(combo)]
+    }
+
+    /// set weight of a combo, clamped to 0..=1
+    pub fn set_weight(&mut self, combo: [Card;2], weight: f32) {
+        self.weights[combo_index(combo)] = weight.clamp(0.0, 1.0);
+    }
+
+    /// set weight of all combos of a class
+    pub fn set_class_weight(&mut self, class: HandClass, weight: f32) {
+        for combo in class.combos() {
+            self.set_weight(combo, weight);
+        }
+    }
+
+    /// weight by combo index
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// count of combos in range
+    pub fn len(&self) -> usize {
+        self.weights.iter().filter(|&&w| w > 0.0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// weighted count of combos: sum of weights
+    pub fn combo_count(&self) -> f64 {
+        self.weights.iter().map(|&w| w as f64).sum()
+    }
+
+    /// iterate combos in range with their weights
+    pub fn combos(&self) -> impl Iterator<Item = ([Card;2], f32)> + '_ {
+        self.weights.iter().enumerate()
+            .filter(|(_, &w)| w > 0.0)
+            .map(|(i, &w)| (combo_from_index(i), w))
+    }
+
+    /// iterate combos in range which don't use the dead cards
+    pub fn combos_without(&self, dead: CardSet) -> impl Iterator<Item = ([Card;2], f32)> + '_ {
+        self.combos().filter(move |(combo, _)| !dead.contains(combo[0]) && !dead.contains(combo[1]))
+    }
+
+    /// a copy without combos using the dead cards
+    pub fn without(&self, dead: CardSet) -> Range {
+        let mut range = Range::new();
+        for (combo, weight) in self.combos_without(dead) {
+            range.set_weight(combo, weight);
+        }
+        range
+    }
+
+    /// union, weight is the max of both
+    pub fn union(&self, other: &Range) -> Range {
+        Range{weights: self.weights.iter().zip(other.weights.iter()).map(|(a, b)| a.max(*b)).collect()}
+    }
+
+    /// intersection, weight is the min of both
+    pub fn intersection(&self, other: &Range) -> Range {
+        Range{weights: self.weights.iter().zip(other.weights.iter()).map(|(a, b)| a.min(*b)).collect()}
+    }
+
+    // weight of the class if all its combos have the same weight
+    fn class_weight(&self, class: HandClass) -> Option<f32> {
+        let combos = class.combos();
+        let weight = self.weight(combos[0]);
+        if combos.iter().all(|&c| self.weight(c) == weight) {Some(weight)} else {None}
+    }
+}
+
+// "AK", "AKs", "AKo", "TT" => (high, low, kind)
+fn parse_class(s: &str) -> Option<(Value, Value, Option<HandClassKind>)> {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() < 2 || chars.len() > 3 {
+        return None;
+    }
+    let a = Value::from_char(chars[0]).ok()?;
+    let b = Value::from_char(chars[1]).ok()?;
+    let (high, low) = if a >= b {(a, b)} else {(b, a)};
+    let kind = match chars.get(2).map(|c| c.to_ascii_lowercase()) {
+        None => if high == low {Some(HandClassKind::Pair)} else {None},
+        Some('s') if high != low => Some(HandClassKind::Suited),
+        Some('o') if high != low => Some(HandClassKind::Offsuit),
+        _ => return None,
+    };
+    Some((high, low, kind))
+}
+
+// classes of (high, low) with optional kind, None means both suited and offsuit
+fn classes_of(high: Value, low: Value, kind: Option<HandClassKind>) -> Vec<HandClass> {
+    match kind {
+        Some(kind) => vec![HandClass{high, low, kind}],
+        None => vec![HandClass{high, low, kind: HandClassKind::Suited},
+                     HandClass{high, low, kind: HandClassKind::Offsuit}],
+    }
+}
+
+// parse an entry without weight to combos
+fn parse_entry(entry: &str) -> Option<Vec<[Card;2]>> {
+    // specific combo, "JK" with a joker is the class KJ
+    if let Ok(cards) = parse_cards::<Vec<Card>>(entry) {
+        if !cards.iter().any(|c| c.is_joker()) {
+            if cards.len() != 2 || combo_index([cards[0], cards[1]]) >= COMBO_COUNT {
+                return None;
+            }
+            return Some(vec![[cards[0], cards[1]]]);
+        }
+    }
+
+    let mut classes: Vec<HandClass> = Vec::new();
+    if let Some(pos) = entry.find('-') {
+        let (high, high_low, high_kind) = parse_class(entry[..pos].trim())?;
+        let (low, low_low, low_kind) = parse_class(entry[pos+1..].trim())?;
+        if high_kind == Some(HandClassKind::Pair) && low_kind == Some(HandClassKind::Pair) {
+            let (from, to) = (high.index().min(low.index()), high.index().max(low.index()));
+            for v in from..=to {
+                let v = Value::from_index(v);
+                classes.push(HandClass{high: v, low: v, kind: HandClassKind::Pair});
+            }
+        }else if high == low && high_kind == low_kind && high_kind != Some(HandClassKind::Pair) {
+            let (from, to) = (high_low.index().min(low_low.index()), high_low.index().max(low_low.index()));
+            for v in from..=to {
+                classes.extend(classes_of(high, Value::from_index(v), high_kind));
+            }
+        }else{
+            return None;
+        }
+    }else if let Some(class) = entry.strip_suffix('+') {
+        let (high, low, kind) = parse_class(class.trim())?;
+        if kind == Some(HandClassKind::Pair) {
+            for v in high.index()..13 {
+                let v = Value::from_index(v);
+                classes.push(HandClass{high: v, low: v, kind: HandClassKind::Pair});
+            }
+        }else{
+            for v in low.index()..high.index() {
+                classes.extend(classes_of(high, Value::from_index(v), kind));
+            }
+        }
+    }else{
+        let (high, low, kind) = parse_class(entry)?;
+        classes.extend(classes_of(high, low, kind));
+    }
+    Some(classes.iter().flat_map(|c| c.combos()).collect())
+}
+
+// "50%", "50" => 0.5 ; fraction when after ':'
+fn parse_weight(s: &str, percent: bool) -> Option<f32> {
+    let s = s.trim();
+    let (number, percent) = match s.strip_suffix('%') {
+        Some(number) => (number.trim(), true),
+        None => (s, percent),
+    };
+    let weight: f32 = number.parse().ok()?;
+    let weight = if percent {weight / 100.0} else {weight};
+    if (0.0..=1.0).contains(&weight) {Some(weight)} else {None}
+}
+
+impl FromStr for Range {
+    type Err = RangeParseError;
+
+    fn from_str(s: &str) -> Result<Range, RangeParseError> {
+        let mut range = Range::new();
+        let mut position = 0;
+        for entry in s.split(',') {
+            // char positions, same as CardStringConvertError
+            let entry_position = position + entry.chars().count() - entry.trim_start().chars().count();
+            position += entry.chars().count() + 1;
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let (hands, weight) = match entry.find(['@', ':']) {
+                Some(pos) => {
+                    let weight = parse_weight(&entry[pos+1..], entry[pos..].starts_with('@'))
+                        .ok_or_else(|| RangeParseError::InvalidWeight{entry: entry.to_string(), position: entry_position})?;
+                    (entry[..pos].trim(), weight)
+                }
+                None => (entry, 1.0),
+            };
+            let combos = parse_entry(hands)
+                .ok_or_else(|| RangeParseError::InvalidEntry{entry: entry.to_string(), position: entry_position})?;
+            for combo in combos {
+                range.set_weight(combo, weight);
+            }
+        }
+        Ok(range)
+    }
+}
+
+// "@50%" suffix for weight not 1
+fn weight_suffix(weight: f32) -> String {
+    if weight >= 1.0 {
+        return String::new();
+    }
+    let percent = format!("{:.2}", weight * 100.0);
+    let percent = percent.trim_end_matches('0').trim_end_matches('.');
+    format!("@{}%", percent)
+}
+
+impl fmt::Display for Range {
+    /// compact notation, entries are grouped by weight from high to low
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut weights: Vec<f32> = self.weights.iter().cloned().filter(|&w| w > 0.0).collect();
+        weights.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        weights.dedup();
+
+        let mut entries: Vec<String> = Vec::new();
+        for &weight in weights.iter() {
+            let suffix = weight_suffix(weight);
+            let whole = |class: HandClass| self.class_weight(class) == Some(weight);
+
+            // pairs from AA down
+            let mut v = 13;
+            while v > 0 {
+                v -= 1;
+                if !whole(HandClass::from_index(v * 13 + v)) {
+                    continue;
+                }
+                let top = v;
+                while v > 0 && whole(HandClass::from_index((v-1) * 13 + (v-1))) {
+                    v -= 1;
+                }
+                let (top, bottom) = (Value::from_index(top as u8), Value::from_index(v as u8));
+                entries.push(if top == bottom {
+                    format!("{}{}{}", top, bottom, suffix)
+                }else if top == Value::Ace {
+                    format!("{}{}+{}", bottom, bottom, suffix)
+                }else{
+                    format!("{}{}-{}{}{}", top, top, bottom, bottom, suffix)
+                });
+            }
+
+            // suited and offsuit, kickers from high to low
+            for high in (1..13u8).rev() {
+                for (kind, mark) in [(HandClassKind::Suited, "s"), (HandClassKind::Offsuit, "o")].iter() {
+                    let class = |low: u8| HandClass{high: Value::from_index(high), low: Value::from_index(low), kind: *kind};
+                    let mut low = high;
+                    while low > 0 {
+                        low -= 1;
+                        if !whole(class(low)) {
+                            continue;
+                        }
+                        let top = low;
+                        while low > 0 && whole(class(low - 1)) {
+                            low -= 1;
+                        }
+                        let h = Value::from_index(high);
+                        let (top, bottom) = (Value::from_index(top), Value::from_index(low));
+                        entries.push(if top == bottom {
+                            format!("{}{}{}{}", h, top, mark, suffix)
+                        }else if top.index() + 1 == high {
+                            format!("{}{}{}+{}", h, bottom, mark, suffix)
+                        }else{
+                            format!("{}{}{}-{}{}{}{}", h, top, mark, h, bottom, mark, suffix)
+                        });
+                    }
+                }
+            }
+
+            // combos of partial classes
+            for (index, &w) in self.weights.iter().enumerate() {
+                let combo = combo_from_index(index);
+                if w == weight && HandClass::of(combo).and_then(|class| self.class_weight(class)).is_none() {
+                    entries.push(format!("{}{}{}", combo[0], combo[1], suffix));
+                }
+            }
+        }
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combo_index(){
+        for index in 0..COMBO_COUNT {
+            let combo = combo_from_index(index);
+            assert_eq!(combo_index(combo), index);
+            assert_eq!(combo_index([combo[1], combo[0]]), index);
+        }
+        for index in 0..HAND_CLASS_COUNT {
+            assert_eq!(HandClass::from_index(index).index(), index);
+        }
+        assert_eq!((0..HAND_CLASS_COUNT).map(|i| HandClass::from_index(i).combo_count()).sum::<usize>(), COMBO_COUNT);
+        assert_eq!("AKs".parse::<HandClass>().unwrap().to_string(), "AKs");
+        let combo = combo_from_index(COMBO_COUNT - 1);
+        assert_eq!(HandClass::of(combo).unwrap().to_string(), "AA");
+        assert_eq!(HandClass::of([Card::JOKER, combo[0]]), None);
+    }
+
+    #[test]
+    fn test_parse_range(){
+        let range: Range = "22+".parse().unwrap();
+        assert_eq!(range.len(), 78);
+        let range: Range = "AK, AhKh@50%, KTo+".parse().unwrap();
+        assert_eq!(range.combo_count(), 16.0 - 0.5 + 3.0 * 12.0);
+        assert_eq!(range.to_string(), "AKo, KTo+, AsKs, AcKc, AdKd, AhKh@50%");
+        assert!("AKx".parse::<Range>().is_err());
+        assert_eq!("TT, 22@150%".parse::<Range>(),
+                   Err(RangeParseError::InvalidWeight{entry: "22@150%".to_string(), position: 4}));
+        assert_eq!("TT, ZZ".parse::<Range>(),
+                   Err(RangeParseError::InvalidEntry{entry: "ZZ".to_string(), position: 4}));
+        assert_eq!("A♠K♠,\u{3000}ZZ".parse::<Range>(),
+                   Err(RangeParseError::InvalidEntry{entry: "ZZ".to_string(), position: 6}));
+
+        // 王牌不是手牌
+        assert_eq!("JkAs".parse::<Range>(),
+                   Err(RangeParseError::InvalidEntry{entry: "JkAs".to_string(), position: 0}));
+        assert_eq!("JK".parse::<Range>().unwrap().to_string(), "KJs, KJo");
+    }
+
+    #[test]
+    fn test_range_algebra(){
+        let a: Range = "TT+".parse().unwrap();
+        let b: Range = "99-JJ:0.5".parse().unwrap();
+        assert_eq!(a.union(&b).to_string(), "TT+, 99@50%");
+        assert_eq!(a.intersection(&b).to_string(), "JJ-TT@50%");
+        let dead: CardSet = "As".parse().unwrap();
+        assert_eq!(a.combos_without(dead).count(), 27);
+        assert_eq!(a.without(dead).len(), 27);
+    }
+}
@@ -0,0 +1,56 @@
+/// A random number generator used by shuffling and sampling
+///
+/// Implement it to plug in any rng, e.g. `rand::RngCore` by a wrapper.
+pub trait Rng {
+    /// next random u64
+    fn next_u64(&mut self) -> u64;
+
+    /// uniform random number in `0..n`, n should > 0
+    fn gen_range(&mut self, n: u64) -> u64 {
+        // rejection sampling, no modulo bias
+        let zone = u64::MAX - (u64::MAX - n + 1) % n;
+        loop {
+            let v = self.next_u64();
+            if v <= zone {
+                return v % n;
+            }
+        }
+    }
+
+    /// uniform random float in `[0, 1)`
+    fn gen_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A small deterministic rng (xorshift64*)
+///
+/// The same seed always gives the same sequence, so shuffles and simulations
+/// are reproducible. It is not for cryptographic use.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        // splitmix64 the seed, so near seeds (0,1,2...) give unrelated sequences
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        SeededRng{
+            state: if z == 0 {0x9E37_79B9_7F4A_7C15} else {z},
+        }
+    }
+}
+
+impl Rng for SeededRng {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
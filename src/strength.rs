@@ -0,0 +1,305 @@
+use crate::Evaluator;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::equity::{should_stop, used_cards, EquityError, MonteCarloOptions};
+use crate::range::Range;
+use crate::rng::{Rng, SeededRng};
+use crate::tools::combination::CombinationIter;
+use std::cmp::Ordering;
+use std::time::Instant;
+
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+/// Hand strength metrics of hole cards on a flop, turn or river
+///
+/// All opponent combos are weighted by the range, runouts are uniform.
+/// See Billings et al., "The challenge of poker" and Johanson, "Robust strategies and counter-strategies".
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandStrength {
+    /// HS: share of the range beaten on current board, ties count half
+    pub hs: f64,
+    /// PPot: chance of being ahead at river when behind now, ties count half
+    pub ppot: f64,
+    /// NPot: chance of being behind at river when ahead now, ties count half
+    pub npot: f64,
+    /// EHS: hs * (1 - npot) + (1 - hs) * ppot
+    pub ehs: f64,
+    /// EHS²: mean of squared river hand strength over runouts
+    pub ehs2: f64,
+    /// runouts counted, 1 on river; runouts blocking every opponent combo are skipped
+    pub runouts: u64,
+}
+
+// 当前的比较结果和河牌的比较结果的加权统计
+struct Potential<T> {
+    hole: [T;7],
+    opponents: Vec<[T;7]>,
+    masks: Vec<CardSet>,
+    weights: Vec<f64>,
+    now: Vec<usize>,
+    board_len: usize,
+    hp: [[f64;3];3],
+    hs2_sum: f64,
+    hs2_square_sum: f64,
+    runouts: u64,
+}
+
+#[inline]
+fn outcome(ours: u32, theirs: u32) -> usize {
+    match ours.cmp(&theirs) {
+        Ordering::Greater => AHEAD,
+        Ordering::Equal => TIED,
+        Ordering::Less => BEHIND,
+    }
+}
+
+impl<T: Copy> Potential<T> {
+    // check cards, eval all opponent combos on current board
+    fn new<E: Evaluator<CardType=T>>(
+        evaluator: &E, hole: [Card;2], board: &[Card], range: &Range, dead: CardSet,
+    ) -> Result<(Potential<T>, CardSet), EquityError> {
+        if board.len() < 3 || board.len() > 5 {
+            return Err(EquityError::InvalidBoard(board.len()));
+        }
+        let used = used_cards(hole.iter().chain(board.iter()), dead)?;
+        let make = |first: [Card;2]| {
+            let mut cards = [evaluator.make_card(&first[0]);7];
+            cards[1] = evaluator.make_card(&first[1]);
+            for (i, card) in board.iter().enumerate() {
+                cards[2+i] = evaluator.make_card(card);
+            }
+            cards
+        };
+        let hole_cards = make(hole);
+        let ours = evaluator.eval(&hole_cards[..2 + board.len()]);
+        let mut potential = Potential{
+            hole: hole_cards, opponents: vec![], masks: vec![], weights: vec![], now: vec![],
+            board_len: board.len(), hp: [[0.0;3];3], hs2_sum: 0.0, hs2_square_sum: 0.0, runouts: 0,
+        };
+        for (combo, weight) in range.combos_without(used) {
+            if weight <= 0.0 {
+                continue;
+            }
+            let cards = make(combo);
+            potential.now.push(outcome(ours, evaluator.eval(&cards[..2 + board.len()])));
+            potential.opponents.push(cards);
+            potential.masks.push(combo[..].into());
+            potential.weights.push(weight as f64);
+        }
+        if potential.opponents.is_empty() {
+            return Err(EquityError::EmptyRange(0));
+        }
+        Ok((potential, used))
+    }
+
+    // add a runout, skipped if it blocks every opponent combo
+    fn add_runout<E: Evaluator<CardType=T>>(&mut self, evaluator: &E, runout: &[Card], runout_mask: CardSet) {
+        for (i, card) in runout.iter().enumerate() {
+            self.hole[2 + self.board_len + i] = evaluator.make_card(card);
+        }
+        let ours = evaluator.eval(&self.hole);
+        let mut river = [0.0;3];
+        for c in 0..self.opponents.len() {
+            if !self.masks[c].is_disjoint(runout_mask) {
+                continue;
+            }
+            let cards = &mut self.opponents[c];
+            for (i, card) in runout.iter().enumerate() {
+                cards[2 + self.board_len + i] = evaluator.make_card(card);
+            }
+            let later = outcome(ours, evaluator.eval(&cards[..]));
+            self.hp[self.now[c]][later] += self.weights[c];
+            river[later] += self.weights[c];
+        }
+        let total = river[AHEAD] + river[TIED] + river[BEHIND];
+        if total == 0.0 {
+            return;
+        }
+        let hs = (river[AHEAD] + river[TIED] / 2.0) / total;
+        self.hs2_sum += hs * hs;
+        self.hs2_square_sum += hs * hs * hs * hs;
+        self.runouts += 1;
+    }
+
+    fn result(&self) -> HandStrength {
+        let mut now = [0.0;3];
+        for (c, &weight) in self.weights.iter().enumerate() {
+            now[self.now[c]] += weight;
+        }
+        let hs = (now[AHEAD] + now[TIED] / 2.0) / (now[AHEAD] + now[TIED] + now[BEHIND]);
+        let hp = &self.hp;
+        let totals: Vec<f64> = hp.iter().map(|row| row.iter().sum()).collect();
+        let ratio = |numerator: f64, denominator: f64| if denominator > 0.0 {numerator / denominator} else {0.0};
+        let ppot = ratio(hp[BEHIND][AHEAD] + hp[BEHIND][TIED] / 2.0 + hp[TIED][AHEAD] / 2.0,
+                         totals[BEHIND] + totals[TIED] / 2.0);
+        let npot = ratio(hp[AHEAD][BEHIND] + hp[TIED][BEHIND] / 2.0 + hp[AHEAD][TIED] / 2.0,
+                         totals[AHEAD] + totals[TIED] / 2.0);
+        HandStrength{
+            hs,
+            ppot,
+            npot,
+            ehs: hs * (1.0 - npot) + (1.0 - hs) * ppot,
+            ehs2: ratio(self.hs2_sum, self.runouts as f64),
+            runouts: self.runouts,
+        }
+    }
+
+    // standard error of ehs2
+    fn std_error(&self) -> f64 {
+        let n = self.runouts as f64;
+        if n < 2.0 {
+            return 1.0;
+        }
+        let mean = self.hs2_sum / n;
+        let variance = (self.hs2_square_sum / n - mean * mean).max(0.0) * n / (n - 1.0);
+        (variance / n).sqrt()
+    }
+}
+
+/// Hand strength metrics by enumerating every runout
+///
+/// - hole: our hole cards
+/// - board: flop, turn or river
+/// - range: opponent's range, `Range::full()` for a random hand
+/// - dead: cards known not in deck
+///
+/// On the flop it's C(47,2) = 1081 runouts for every opponent combo.
+///
+/// Example:
+/// ```
+/// use pokerlib::card::parse_cards;
+/// use pokerlib::{Card, CardSet, NativeEvaluator, Range};
+/// use pokerlib::strength::hand_strength;
+///
+/// let hole: Vec<Card> = parse_cards("AsKs").unwrap();
+/// let board: Vec<Card> = parse_cards("Qs7s2d").unwrap();
+/// let strength = hand_strength(&NativeEvaluator::new(), [hole[0], hole[1]], &board, &Range::full(), CardSet::new()).unwrap();
+/// assert!(strength.ppot > 0.3);
+/// assert!(strength.ehs > strength.hs);
+/// ```
+pub fn hand_strength<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hole: [Card;2],
+    board: &[Card],
+    range: &Range,
+    dead: CardSet,
+) -> Result<HandStrength, EquityError> {
+    let (mut potential, used) = Potential::new(evaluator, hole, board, range, dead)?;
+    let deck = (!used).to_vec();
+    let missing = 5 - board.len();
+    if missing == 0 {
+        potential.add_runout(evaluator, &[], CardSet::new());
+    }else{
+        let mut it = CombinationIter::new(deck.len(), missing);
+        let mut indexes = [0;2];
+        while it.move_next(&mut indexes) {
+            let runout: Vec<Card> = indexes[..missing].iter().map(|&i| deck[i]).collect();
+            potential.add_runout(evaluator, &runout, runout[..].into());
+        }
+    }
+    Ok(potential.result())
+}
+
+/// Estimate hand strength metrics by sampling runouts
+///
+/// HS is exact, the potentials and EHS² are from sampled runouts. Arguments are same as
+/// `hand_strength`, `options.target_std_error` is for EHS².
+pub fn monte_carlo_hand_strength<T: Copy, E: Evaluator<CardType=T>>(
+    evaluator: &E,
+    hole: [Card;2],
+    board: &[Card],
+    range: &Range,
+    dead: CardSet,
+    options: &MonteCarloOptions,
+) -> Result<HandStrength, EquityError> {
+    if options.max_iterations.is_none() && options.max_duration.is_none() && options.target_std_error.is_none() {
+        return Err(EquityError::NoStopCondition);
+    }
+    let (mut potential, used) = Potential::new(evaluator, hole, board, range, dead)?;
+    let mut deck = (!used).to_vec();
+    let missing = 5 - board.len();
+    if missing == 0 {
+        potential.add_runout(evaluator, &[], CardSet::new());
+        return Ok(potential.result());
+    }
+
+    let mut rng = SeededRng::new(options.seed);
+    let start = Instant::now();
+    let check_interval = options.check_interval.max(1);
+    loop {
+        for i in 0..missing {
+            let j = i + rng.gen_range((deck.len() - i) as u64) as usize;
+            deck.swap(i, j);
+        }
+        let runout = &deck[..missing];
+        potential.add_runout(evaluator, runout, runout.into());
+        let iterations = potential.runouts;
+        if (iterations % check_interval == 0 || options.max_iterations == Some(iterations))
+            && should_stop(options, start, iterations, || vec![potential.std_error()]) {
+            break;
+        }
+    }
+    Ok(potential.result())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NativeEvaluator;
+    use crate::card::parse_cards;
+    use crate::test_util::hand;
+
+    #[test]
+    fn test_hand_strength(){
+        let evaluator = NativeEvaluator::new();
+        let board: Vec<Card> = parse_cards("Ah7d2cKs9h").unwrap();
+        let nuts = hand_strength(&evaluator, hand("AsAd"), &board, &Range::full(), CardSet::new()).unwrap();
+        assert_eq!((nuts.hs, nuts.ppot, nuts.npot, nuts.runouts), (1.0, 0.0, 0.0, 1));
+        assert_eq!(nuts.ehs2, 1.0);
+
+        // 同花听牌: 现在落后于对子, 但潜力大
+        let board: Vec<Card> = parse_cards("Qs7s2d").unwrap();
+        let draw = hand_strength(&evaluator, hand("5s4s"), &board, &Range::full(), CardSet::new()).unwrap();
+        let made = hand_strength(&evaluator, hand("QdQc"), &board, &Range::full(), CardSet::new()).unwrap();
+        assert!(draw.hs < 0.3 && draw.ppot > 0.3);
+        assert!(made.hs > 0.95 && made.npot < 0.15);
+        assert!((draw.ehs - (draw.hs * (1.0 - draw.npot) + (1.0 - draw.hs) * draw.ppot)).abs() < 1e-12);
+        assert_eq!(draw.runouts, 1081);
+
+        // 对手只有 AA 时, KK 落后, 需要 K
+        let kk = hand_strength(&evaluator, hand("KhKd"), &board, &"AA".parse().unwrap(), CardSet::new()).unwrap();
+        assert_eq!(kk.hs, 0.0);
+        assert!(kk.ppot > 0.05 && kk.ppot < 0.15);
+
+        let options = MonteCarloOptions{seed: 3, max_iterations: Some(300), ..Default::default()};
+        let sampled = monte_carlo_hand_strength(&evaluator, hand("5s4s"), &board, &Range::full(), CardSet::new(), &options).unwrap();
+        assert_eq!(sampled.hs, draw.hs);
+        assert_eq!(sampled.runouts, 300);
+        assert!((sampled.ehs - draw.ehs).abs() < 0.05);
+
+        // 挡住对手所有组合的河牌不算
+        let turn: Vec<Card> = parse_cards("Qs7s2dJh").unwrap();
+        let one = hand_strength(&evaluator, hand("AsKs"), &turn, &"ThTd".parse().unwrap(), CardSet::new()).unwrap();
+        assert_eq!(one.runouts, 44);
+        let mut hs2 = 0.0;
+        for card in (!CardSet::from(&parse_cards::<Vec<Card>>("AsKsQs7s2dJhThTd").unwrap()[..])).iter() {
+            let mut ours: Vec<Card> = parse_cards("AsKsQs7s2dJh").unwrap();
+            let mut theirs: Vec<Card> = parse_cards("ThTdQs7s2dJh").unwrap();
+            ours.push(card);
+            theirs.push(card);
+            let (a, b) = (evaluator.simple_eval(&ours), evaluator.simple_eval(&theirs));
+            let hs: f64 = if a > b {1.0} else if a == b {0.5} else {0.0};
+            hs2 += hs * hs / 44.0;
+        }
+        assert!((one.ehs2 - hs2).abs() < 1e-12);
+
+        assert_eq!(hand_strength(&evaluator, hand("5s4s"), &[], &Range::full(), CardSet::new()),
+                   Err(EquityError::InvalidBoard(0)));
+        assert_eq!(hand_strength(&evaluator, hand("JkAs"), &board, &Range::full(), CardSet::new()),
+                   Err(EquityError::InvalidCard(Card::JOKER)));
+        assert_eq!(hand_strength(&evaluator, hand("5s4s"), &board, &"55".parse::<Range>().unwrap().without(CardSet::full()), CardSet::new()),
+                   Err(EquityError::EmptyRange(0)));
+    }
+}
@@ -0,0 +1,8 @@
+// 测试里共用的函数
+use crate::card::{parse_cards, Card};
+
+// 两张手牌, 如 "AsKd"
+pub(crate) fn hand(s: &str) -> [Card;2] {
+    let cards: Vec<Card> = parse_cards(s).unwrap();
+    [cards[0], cards[1]]
+}
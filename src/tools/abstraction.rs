@@ -0,0 +1,272 @@
+use crate::IncrementalEvaluator;
+use crate::abstraction::{equity_bin, weighted_kmeans_emd, CardAbstraction};
+use crate::card::{Card, CardIndexPolicy};
+use crate::card_set::CardSet;
+use crate::deck::Street;
+use crate::hand_indexer::{canonical_boards, HandIndexer};
+use crate::range::{combo_from_index, COMBO_COUNT};
+use crate::tools::combination::{CombinationIter, Combos};
+
+/// Options of `build_abstraction`
+#[derive(Debug, Clone)]
+pub struct AbstractionOptions {
+    /// histogram bins over equity 0..=1
+    pub bins: usize,
+    /// buckets count, at most 65536
+    pub buckets: usize,
+    /// max k-means rounds
+    pub iterations: usize,
+    /// seed of k-means++ initialization
+    pub seed: u64,
+}
+
+impl Default for AbstractionOptions {
+    fn default() -> Self {
+        AbstractionOptions{bins: 30, buckets: 50, iterations: 30, seed: 0}
+    }
+}
+
+// 一个河牌公共牌上所有手牌对 random 的胜率, live 是不冲突的手牌
+//
+// 按牌值排序后扫描, 比它小的和相等的手牌数减去有相同牌的
+fn river_equities(combos: &Combos, values: &[u32], live: &mut [usize], equities: &mut [f64]) {
+    live.sort_unstable_by_key(|&c| values[c]);
+    let mut total_by_card = [0u32; 52];
+    for &c in live.iter() {
+        total_by_card[combos.cards[c][0]] += 1;
+        total_by_card[combos.cards[c][1]] += 1;
+    }
+    let mut lower = 0;
+    let mut lower_by_card = [0u32; 52];
+    let mut group_by_card = [0u32; 52];
+    let mut start = 0;
+    while start < live.len() {
+        let end = start + live[start..].iter().take_while(|&&c| values[c] == values[live[start]]).count();
+        for &c in &live[start..end] {
+            group_by_card[combos.cards[c][0]] += 1;
+            group_by_card[combos.cards[c][1]] += 1;
+        }
+        for &c in &live[start..end] {
+            let [a, b] = combos.cards[c];
+            // 有相同牌的手牌 (包括自己) 不能对局
+            let wins = lower - lower_by_card[a] - lower_by_card[b];
+            let ties = (end - start) as u32 + 1 - group_by_card[a] - group_by_card[b];
+            let total = live.len() as u32 + 1 - total_by_card[a] - total_by_card[b];
+            equities[c] = (wins as f64 + ties as f64 / 2.0) / total as f64;
+        }
+        for &c in &live[start..end] {
+            for &card in combos.cards[c].iter() {
+                lower_by_card[card] += 1;
+                group_by_card[card] = 0;
+            }
+        }
+        lower += (end - start) as u32;
+        start = end;
+    }
+}
+
+// 河牌公共牌上所有不冲突的手牌的胜率
+fn eval_river<E: IncrementalEvaluator>(
+    evaluator: &E, combos: &Combos, deck: &[E::CardType], board: &[Card],
+    values: &mut [u32], live: &mut Vec<usize>, equities: &mut [f64],
+) where E::CardType: Copy {
+    let board_mask: CardSet = board.iter().collect();
+    let state = board.iter().fold(evaluator.start(), |state, c| {
+        evaluator.add_card(state, deck[c.card_index(CardIndexPolicy::GroupByValue) as usize])
+    });
+    live.clear();
+    for (c, value) in values.iter_mut().enumerate() {
+        if !combos.masks[c].is_disjoint(board_mask) {
+            continue;
+        }
+        let [a, b] = combos.cards[c];
+        *value = evaluator.finish(evaluator.add_card(evaluator.add_card(state, deck[a]), deck[b]));
+        live.push(c);
+    }
+    river_equities(combos, values, live, equities);
+}
+
+// 一个公共牌上所有手牌的直方图计数, 枚举到河牌的所有发牌
+fn board_counts<E: IncrementalEvaluator>(
+    evaluator: &E, combos: &Combos, deck: &[E::CardType], board: &[Card], bins: usize, counts: &mut [u32],
+) where E::CardType: Copy {
+    let rest = (!board.iter().collect::<CardSet>()).to_vec();
+    let missing = 5 - board.len();
+    let mut values = vec![0u32; COMBO_COUNT];
+    let mut equities = vec![0.0; COMBO_COUNT];
+    let mut live = Vec::with_capacity(COMBO_COUNT);
+    let mut river = board.to_vec();
+    let mut it = CombinationIter::new(rest.len(), missing);
+    let mut indexes = [0usize;2];
+    loop {
+        if missing > 0 && !it.move_next(&mut indexes) {
+            break;
+        }
+        river.truncate(board.len());
+        river.extend(indexes[..missing].iter().map(|&i| rest[i]));
+        eval_river(evaluator, combos, deck, &river, &mut values, &mut live, &mut equities);
+        for &c in live.iter() {
+            counts[c * bins + equity_bin(equities[c], bins)] += 1;
+        }
+        if missing == 0 {
+            break;
+        }
+    }
+}
+
+// 河牌每个手牌的胜率所在的 bin, 按 hold_em_street(River) 的序号
+fn river_bins<E: IncrementalEvaluator>(evaluator: &E, bins: usize) -> Vec<u16> where E::CardType: Copy {
+    assert!(bins <= u16::MAX as usize + 1, "too many bins");
+    let combos = Combos::new();
+    let deck: Vec<E::CardType> = Card::one_desk_cards().iter().map(|c| evaluator.make_card(c)).collect();
+    let hands = HandIndexer::hold_em_street(Street::River);
+    let boards = HandIndexer::new(&[5]).unwrap();
+    let mut result = vec![0u16; hands.size(1) as usize];
+    let mut values = vec![0u32; COMBO_COUNT];
+    let mut equities = vec![0.0; COMBO_COUNT];
+    let mut live = Vec::with_capacity(COMBO_COUNT);
+    let mut cards = vec![Card::JOKER; 7];
+    for index in 0..boards.size(0) {
+        let board = boards.unindex(0, index).unwrap();
+        eval_river(evaluator, &combos, &deck, &board, &mut values, &mut live, &mut equities);
+        cards[2..].copy_from_slice(&board);
+        for &c in live.iter() {
+            let combo = combo_from_index(c);
+            cards[0] = combo[0];
+            cards[1] = combo[1];
+            result[hands.index(&cards).unwrap() as usize] = equity_bin(equities[c], bins) as u16;
+        }
+    }
+    result
+}
+
+/// equity histograms of all hands of a street, flattened by `bins`, in `HandIndexer::hold_em_street` order
+///
+/// Same histograms as `abstraction::equity_histogram`, computed board by board: every
+/// suit isomorphic board is enumerated once, all combos are evaluated on each river.
+/// The histograms take `4 * bins` bytes per hand, about 150MB for the flop and 1.7GB
+/// for the turn with 30 bins, the river ones are too large for most machines.
+///
+/// The evaluator must be an `IncrementalEvaluator` (`NativeEvaluator` or `TwoPlusTwoEvaluator`):
+/// the board state is shared by all combos, other evaluators are not supported.
+pub fn street_histograms<E: IncrementalEvaluator>(evaluator: &E, street: Street, bins: usize) -> Vec<f32>
+where E::CardType: Copy {
+    assert!(bins > 0, "need at least one bin");
+    let combos = Combos::new();
+    let deck: Vec<E::CardType> = Card::one_desk_cards().iter().map(|c| evaluator.make_card(c)).collect();
+    let hands = HandIndexer::hold_em_street(street);
+    let mut histograms = vec![0.0f32; hands.size(hands.rounds() - 1) as usize * bins];
+
+    if street == Street::Preflop {
+        // 同构的河牌公共牌只算一次, 手牌类型的计数乘以同构的数量
+        let (boards, weights) = canonical_boards(5);
+        let classes: Vec<usize> = (0..COMBO_COUNT).map(|c| hands.index(&combo_from_index(c)).unwrap() as usize).collect();
+        let mut counts = vec![0.0f64; histograms.len()];
+        let mut values = vec![0u32; COMBO_COUNT];
+        let mut equities = vec![0.0; COMBO_COUNT];
+        let mut live = Vec::with_capacity(COMBO_COUNT);
+        for (index, &weight) in weights.iter().enumerate() {
+            let board = boards.unindex(0, index as u64).unwrap();
+            eval_river(evaluator, &combos, &deck, &board, &mut values, &mut live, &mut equities);
+            for &c in live.iter() {
+                counts[classes[c] * bins + equity_bin(equities[c], bins)] += weight as f64;
+            }
+        }
+        for (histogram, count) in histograms.chunks_exact_mut(bins).zip(counts.chunks_exact(bins)) {
+            let total: f64 = count.iter().sum();
+            for (h, &n) in histogram.iter_mut().zip(count) {
+                *h = (n / total) as f32;
+            }
+        }
+        return histograms;
+    }
+
+    if street == Street::River {
+        for (histogram, &bin) in histograms.chunks_exact_mut(bins).zip(river_bins(evaluator, bins).iter()) {
+            histogram[bin as usize] = 1.0;
+        }
+        return histograms;
+    }
+
+    // 同构的公共牌只算一次, 其上的手牌覆盖所有的序号
+    let boards = HandIndexer::new(&[street.board_len()]).unwrap();
+    let mut counts = vec![0u32; COMBO_COUNT * bins];
+    for index in 0..boards.size(0) {
+        let board = boards.unindex(0, index).unwrap();
+        let board_mask: CardSet = board.iter().collect();
+        counts.iter_mut().for_each(|n| *n = 0);
+        board_counts(evaluator, &combos, &deck, &board, bins, &mut counts);
+        let mut cards = vec![Card::JOKER; 2];
+        cards.extend_from_slice(&board);
+        for c in 0..COMBO_COUNT {
+            if !combos.masks[c].is_disjoint(board_mask) {
+                continue;
+            }
+            let combo = combo_from_index(c);
+            cards[0] = combo[0];
+            cards[1] = combo[1];
+            let hand = hands.index(&cards).unwrap() as usize;
+            let count = &counts[c * bins..(c + 1) * bins];
+            let total: u32 = count.iter().sum();
+            for (h, &n) in histograms[hand * bins..(hand + 1) * bins].iter_mut().zip(count) {
+                *h = n as f32 / total as f32;
+            }
+        }
+    }
+    histograms
+}
+
+/// cluster all hands of a street into buckets by their equity histograms
+///
+/// It's `street_histograms` then `abstraction::kmeans_emd`. On the river every histogram has a
+/// single nonzero bin, so the bins are clustered weighted by their hands count instead, and there
+/// are at most `bins` buckets. Preflop and river take minutes, the flop and turn take much longer
+/// with `TwoPlusTwoEvaluator` in release build. It needs an `IncrementalEvaluator` as `street_histograms`.
+pub fn build_abstraction<E: IncrementalEvaluator>(evaluator: &E, street: Street, options: &AbstractionOptions) -> CardAbstraction
+where E::CardType: Copy {
+    let bins = options.bins;
+    if street == Street::River {
+        let hands = river_bins(evaluator, bins);
+        let mut weights = vec![0.0; bins];
+        for &bin in hands.iter() {
+            weights[bin as usize] += 1.0;
+        }
+        let mut histograms = vec![0.0f32; bins * bins];
+        for bin in 0..bins {
+            histograms[bin * bins + bin] = 1.0;
+        }
+        let clusters = weighted_kmeans_emd(histograms, Some(&weights), bins, options.buckets, options.iterations, options.seed);
+        let buckets = hands.iter().map(|&bin| clusters.assignments[bin as usize]).collect();
+        return CardAbstraction::from_buckets(street, options.buckets.min(bins), buckets).unwrap();
+    }
+    let histograms = street_histograms(evaluator, street, bins);
+    let count = histograms.len() / bins;
+    let clusters = weighted_kmeans_emd(histograms, None, bins, options.buckets, options.iterations, options.seed);
+    CardAbstraction::from_buckets(street, options.buckets.min(count), clusters.assignments).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NativeEvaluator;
+    use crate::abstraction::equity_histogram;
+    use crate::card::parse_cards;
+
+    #[test]
+    fn test_board_counts(){
+        let evaluator = NativeEvaluator::new();
+        let combos = Combos::new();
+        let deck: Vec<u32> = Card::one_desk_cards().iter().map(|c| crate::Evaluator::make_card(&evaluator, c)).collect();
+        let board: Vec<Card> = parse_cards("Qs7s2dJh").unwrap();
+        let mut counts = vec![0u32; COMBO_COUNT * 10];
+        board_counts(&evaluator, &combos, &deck, &board, 10, &mut counts);
+
+        for hole in ["AsKs", "7h7d", "3c2c", "Jc2h"].iter() {
+            let hole: Vec<Card> = parse_cards(hole).unwrap();
+            let c = crate::range::combo_index([hole[0], hole[1]]);
+            let expected = equity_histogram(&evaluator, [hole[0], hole[1]], &board, 10).unwrap();
+            let actual: Vec<f32> = counts[c * 10..(c + 1) * 10].iter().map(|&n| n as f32 / 46.0).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+}
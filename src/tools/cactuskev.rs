@@ -80,7 +80,7 @@ fn save_tables(dir_path: &Path, all_cards_values: &[u32], evaluator: NativeEvalu
     let mut others_count = 0;
 
     for (output_value, &cards_type_value) in all_cards_values.iter().rev().enumerate() {
-        let (tp_type, values) = evaluator.unpack_eval_value(cards_type_value).unwrap();
+        let (tp_type, values) = evaluator.unpack_native_value(cards_type_value).unwrap();
         let output_value = output_value + 1;
 
         match tp_type {
@@ -134,7 +134,7 @@ fn save_tables(dir_path: &Path, all_cards_values: &[u32], evaluator: NativeEvalu
     let mut file = File::create(dir_path.join("tbl_value_contents"))?;
     file.write_all(b"[")?;
     for &value in all_cards_values.iter() {
-        let (_, values) = evaluator.unpack_eval_value(value).unwrap();
+        let (_, values) = evaluator.unpack_native_value(value).unwrap();
         let value = (value & 0xF00000) | values.iter().fold(0, |acc, &b| (acc <<4) | (b.index() as u32+1));
         file.write(format!("{},", value).as_bytes())?;
     }
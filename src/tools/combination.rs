@@ -1,4 +1,6 @@
-use crate::card::Card;
+use crate::card::{Card, CardIndexPolicy};
+use crate::card_set::CardSet;
+use crate::range::{combo_from_index, HandClass, COMBO_COUNT};
 
 /*
 提供组合相关的类
@@ -215,6 +217,36 @@ impl Iterator for CardsByValueCombination {
     }
 }
 
+// 所有两张牌组合 (按 combo_index) 的 mask, 两张牌的序号 (GroupByValue), 手牌类型, 每张牌所在的组合
+pub(crate) struct Combos {
+    pub(crate) masks: Vec<CardSet>,
+    pub(crate) cards: Vec<[usize;2]>,
+    pub(crate) classes: Vec<usize>,
+    pub(crate) by_card: Vec<Vec<usize>>,
+}
+
+impl Combos {
+    pub(crate) fn new() -> Combos {
+        let mut combos = Combos{
+            masks: Vec::with_capacity(COMBO_COUNT),
+            cards: Vec::with_capacity(COMBO_COUNT),
+            classes: Vec::with_capacity(COMBO_COUNT),
+            by_card: vec![vec![]; 52],
+        };
+        for i in 0..COMBO_COUNT {
+            let combo = combo_from_index(i);
+            let cards = [combo[0].card_index(CardIndexPolicy::GroupByValue) as usize,
+                         combo[1].card_index(CardIndexPolicy::GroupByValue) as usize];
+            combos.by_card[cards[0]].push(i);
+            combos.by_card[cards[1]].push(i);
+            combos.masks.push(CardSet::from(&combo[..]));
+            combos.cards.push(cards);
+            combos.classes.push(HandClass::of(combo).unwrap().index());
+        }
+        combos
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,3 +1,5 @@
 pub mod combination;
 pub mod cactuskev;
 pub mod twoplustwo;
+pub mod preflop;
+pub mod abstraction;
@@ -0,0 +1,162 @@
+use crate::IncrementalEvaluator;
+use crate::card::{Card, CardIndexPolicy};
+use crate::card_set::CardSet;
+use crate::hand_indexer::canonical_boards;
+use crate::preflop::PreflopTable;
+use crate::range::{combo_from_index, COMBO_COUNT, HAND_CLASS_COUNT};
+use crate::tools::combination::Combos;
+use std::path::Path;
+
+// 一对手牌类型之间的输和平, 按公共牌的权重累计
+struct Counts {
+    wins: Vec<u64>,
+    ties: Vec<u64>,
+}
+
+impl Counts {
+    fn new() -> Counts {
+        Counts{
+            wins: vec![0; HAND_CLASS_COUNT * HAND_CLASS_COUNT],
+            ties: vec![0; HAND_CLASS_COUNT * HAND_CLASS_COUNT],
+        }
+    }
+}
+
+// 一个公共牌: values 是每个手牌的牌值 (和公共牌冲突的忽略), live 是不冲突的手牌
+//
+// 按牌值排序后扫描, 比它小的每个类型的手牌数加到输赢中, 再去掉和它有相同牌的手牌
+fn add_board(combos: &Combos, board: CardSet, values: &[u32], live: &mut [usize], weight: u64, counts: &mut Counts) {
+    live.sort_unstable_by_key(|&c| values[c]);
+    let mut lower = [0u64; HAND_CLASS_COUNT];
+    let mut group = [0u64; HAND_CLASS_COUNT];
+    let mut start = 0;
+    while start < live.len() {
+        let end = start + live[start..].iter().take_while(|&&c| values[c] == values[live[start]]).count();
+        for &c in &live[start..end] {
+            group[combos.classes[c]] += 1;
+        }
+        for &c in &live[start..end] {
+            let row = combos.classes[c] * HAND_CLASS_COUNT;
+            for (win, &n) in counts.wins[row..row + HAND_CLASS_COUNT].iter_mut().zip(lower.iter()) {
+                *win += weight * n;
+            }
+            for (tie, &n) in counts.ties[row..row + HAND_CLASS_COUNT].iter_mut().zip(group.iter()) {
+                *tie += weight * n;
+            }
+            // 和自己平
+            counts.ties[row + combos.classes[c]] -= weight;
+
+            // 有相同牌的手牌不能对局
+            for &card in combos.cards[c].iter() {
+                for &other in &combos.by_card[card] {
+                    if other == c || !combos.masks[other].is_disjoint(board) {
+                        continue;
+                    }
+                    if values[other] < values[c] {
+                        counts.wins[row + combos.classes[other]] -= weight;
+                    }else if values[other] == values[c] {
+                        counts.ties[row + combos.classes[other]] -= weight;
+                    }
+                }
+            }
+        }
+        for &c in &live[start..end] {
+            lower[combos.classes[c]] += 1;
+            group[combos.classes[c]] = 0;
+        }
+        start = end;
+    }
+}
+
+/// compute the 169x169 preflop equity table by exact enumeration
+///
+/// Every board is enumerated up to suit isomorphism (134459 boards), all combos are evaluated
+/// on it, so card removal is exact. It takes about two minutes with `TwoPlusTwoEvaluator` in release build.
+pub fn generate_table<E: IncrementalEvaluator>(evaluator: &E) -> PreflopTable where E::CardType: Copy {
+    let combos = Combos::new();
+    let deck: Vec<E::CardType> = Card::one_desk_cards().iter().map(|c| evaluator.make_card(c)).collect();
+
+    // 同构的公共牌只算一次, 权重是同构的数量
+    let (indexer, weights) = canonical_boards(5);
+
+    let mut counts = Counts::new();
+    let mut values = vec![0u32; COMBO_COUNT];
+    let mut live = Vec::with_capacity(COMBO_COUNT);
+    for (index, &weight) in weights.iter().enumerate() {
+        let board = indexer.unindex(0, index as u64).unwrap();
+        let board_mask: CardSet = board.iter().collect();
+        let state = board.iter().fold(evaluator.start(), |state, c| evaluator.add_card(state, evaluator.make_card(c)));
+        live.clear();
+        for (c, value) in values.iter_mut().enumerate() {
+            if !combos.masks[c].is_disjoint(board_mask) {
+                continue;
+            }
+            let combo = combo_from_index(c);
+            let cards = [deck[combo[0].card_index(CardIndexPolicy::GroupByValue) as usize],
+                         deck[combo[1].card_index(CardIndexPolicy::GroupByValue) as usize]];
+            *value = evaluator.finish(evaluator.add_card(evaluator.add_card(state, cards[0]), cards[1]));
+            live.push(c);
+        }
+        add_board(&combos, board_mask, &values, &mut live, weight, &mut counts);
+    }
+
+    let equities: Vec<f64> = (0..HAND_CLASS_COUNT * HAND_CLASS_COUNT).map(|i| {
+        let (hero, villain) = (i / HAND_CLASS_COUNT, i % HAND_CLASS_COUNT);
+        let wins = counts.wins[i] as f64;
+        let ties = counts.ties[i] as f64;
+        let losses = counts.wins[villain * HAND_CLASS_COUNT + hero] as f64;
+        (wins + ties / 2.0) / (wins + ties + losses)
+    }).collect();
+    PreflopTable::from_equities(&equities).unwrap()
+}
+
+// 生成翻前胜率表格文件
+pub fn generate_data_file<E: IncrementalEvaluator>(evaluator: &E, path: &Path) -> std::io::Result<()>
+where E::CardType: Copy {
+    generate_table(evaluator).write_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Evaluator, NativeEvaluator};
+    use crate::card::parse_cards;
+
+    #[test]
+    fn test_add_board(){
+        let evaluator = NativeEvaluator::new();
+        let combos = Combos::new();
+        let board: Vec<Card> = parse_cards("AsKd7h7c2s").unwrap();
+        let board_mask: CardSet = board.iter().collect();
+        let mut values = vec![0u32; COMBO_COUNT];
+        let mut live = vec![];
+        for (c, value) in values.iter_mut().enumerate() {
+            if combos.masks[c].is_disjoint(board_mask) {
+                let mut cards = board.clone();
+                cards.extend_from_slice(&combo_from_index(c));
+                *value = evaluator.simple_eval(&cards);
+                live.push(c);
+            }
+        }
+        let mut counts = Counts::new();
+        add_board(&combos, board_mask, &values, &mut live.clone(), 3, &mut counts);
+
+        // 逐对比较
+        let mut expected = Counts::new();
+        for &a in &live {
+            for &b in &live {
+                if !combos.masks[a].is_disjoint(combos.masks[b]) {
+                    continue;
+                }
+                let i = combos.classes[a] * HAND_CLASS_COUNT + combos.classes[b];
+                if values[a] > values[b] {
+                    expected.wins[i] += 3;
+                }else if values[a] == values[b] {
+                    expected.ties[i] += 3;
+                }
+            }
+        }
+        assert_eq!(counts.wins, expected.wins);
+        assert_eq!(counts.ties, expected.ties);
+    }
+}
@@ -1,8 +1,18 @@
-use crate::Evaluator;
-use crate::evaluator::CactusKevEvaluator;
-use std::fs::File;
-use std::path::Path;
-use std::io::prelude::*;
+// twoplustwo 表格生成器, 只依赖 std, build.rs 也直接引入这个文件
+// 求值函数由调用方传入: cactuskev 格式的 5-7 张牌, 返回 1-7462
+
+/// how many u32 entries in the data file
+pub const DATA_FILE_LEN: usize = 32487834;
+
+/// checksum of the data file written by `generate_data_file`
+pub const DATA_FILE_CHECKSUM: u64 = 0x4bc914fcc9bf68d2;
+
+/// FNV-1a over the table entries
+pub fn table_checksum(table: &[u32]) -> u64 {
+    table.iter().fold(0xcbf29ce484222325, |hash, &entry| {
+        (hash ^ entry as u64).wrapping_mul(0x100000001b3)
+    })
+}
 
 struct DataFileGenerator {
     //    ids: [i64;612978],
@@ -164,7 +174,7 @@ impl DataFileGenerator {
         return high;
     }
 
-    fn do_eval(&mut self, idin: i64, evaluator: &CactusKevEvaluator) -> i32 {
+    fn do_eval(&mut self, idin: i64, eval: &dyn Fn(&[u32]) -> u32) -> i32 {
         // converts a 64bit handID to an absolute ranking.
 
         // I guess I have some explaining to do here...
@@ -239,7 +249,7 @@ impl DataFileGenerator {
 
             match numevalcards {  // run Cactus Keys routines
                 5 | 6 | 7 => {
-                    holdrank = evaluator.eval(&wk[0..numevalcards as usize]) as i32
+                    holdrank = eval(&wk[0..numevalcards as usize]) as i32
                 }
                 _ => {// problem!!  shouldn't hit this...
                     println!("    Problem with numcards = {}!!\n", self.numcards);
@@ -266,7 +276,7 @@ impl DataFileGenerator {
         return result;  // now a handrank that I like
     }
 
-    fn generate(&mut self){
+    fn generate(&mut self, eval: &dyn Fn(&[u32]) -> u32){
         // step through the ID array - always shifting the current ID and
         // adding 52 cards to the end of the array.
         // when I am at 7 cards put the Hand Rank in!!
@@ -298,8 +308,6 @@ impl DataFileGenerator {
         println!("\nSetting HandRanks!\n");
 
 
-        let evaluator = CactusKevEvaluator::new();
-
         // this is as above, but will not add anything to the ID list, so it is stable
         id_num = 0;
         while self.ids[id_num as usize] != 0 || id_num == 0 {
@@ -313,7 +321,7 @@ impl DataFileGenerator {
 	            self.save_id(id) * 53 + 53
                 } else {
 	            // if I am at the 7th card, get the equivalence class ("hand rank") to save
-	            self.do_eval(id, &evaluator)
+	            self.do_eval(id, eval)
                 };
 
                 self.max_hr = id_num * 53 + card + 53;	// find where to put it
@@ -325,7 +333,7 @@ impl DataFileGenerator {
                 // you can just do HR[u3] or HR[u4] from below code for Handrank of the 5 or
                 // 6 card hand
                 // this puts the above handrank into the array
-                self.hr[id_num as usize * 53 + 53] = self.do_eval(self.ids[id_num as usize], &evaluator);
+                self.hr[id_num as usize * 53 + 53] = self.do_eval(self.ids[id_num as usize], eval);
             }
 
 	    print!("\rID - {}", id_num);
@@ -388,20 +396,9 @@ impl DataFileGenerator {
     }
 }
 
-// 生成 twoplustwo 表格文件
-pub fn generate_data_file(path: &Path) -> std::io::Result<()>{
+/// generate the twoplustwo table with a cactuskev evaluation function
+pub fn generate_table(eval: &dyn Fn(&[u32]) -> u32) -> Vec<u32> {
     let mut generater = DataFileGenerator::new();
-    generater.generate();
-
-    // output the array now that I have it!!
-    let mut file = File::create(path)?;
-    
-    let ptr :*const i32 = generater.hr.as_ptr();
-    let ptr :*const u8 = ptr as *const u8;
-
-    unsafe {
-        let slice = std::slice::from_raw_parts(ptr, generater.hr.len()*4);
-        file.write(slice)?;
-    }
-    Ok(())
+    generater.generate(eval);
+    generater.hr.into_iter().map(|entry| entry as u32).collect()
 }
@@ -0,0 +1,27 @@
+use crate::Evaluator;
+use crate::evaluator::CactusKevEvaluator;
+use std::fs::File;
+use std::path::Path;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+mod generator;
+
+pub use generator::{table_checksum, DATA_FILE_CHECKSUM, DATA_FILE_LEN};
+
+/// generate the twoplustwo table, same as the content of `generate_data_file`
+pub fn generate_table() -> Vec<u32> {
+    let evaluator = CactusKevEvaluator::new();
+    generator::generate_table(&|cards: &[u32]| evaluator.eval(cards))
+}
+
+// 生成 twoplustwo 表格文件
+pub fn generate_data_file(path: &Path) -> std::io::Result<()>{
+    // output the array now that I have it!!
+    // 固定按小端写入, 和 TwoPlusTwoEvaluator::from_file 对应
+    let mut file = BufWriter::new(File::create(path)?);
+    for entry in generate_table() {
+        file.write_all(&entry.to_le_bytes())?;
+    }
+    file.flush()
+}
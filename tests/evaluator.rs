@@ -1,13 +1,10 @@
-#![feature(test)]
-
 #[macro_use]
 extern crate lazy_static;
 
-extern crate test;
 use pokerlib::cards_type::CardsType;
-use pokerlib::card::Card;
+use pokerlib::card::{parse_cards, Card};
 use std::path::Path;
-use pokerlib::Evaluator;
+use pokerlib::{Evaluator, IncrementalEvaluator};
 use pokerlib::{NativeEvaluator, CactusKevEvaluator, TwoPlusTwoEvaluator};
 use pokerlib::tools::twoplustwo::generate_data_file;
 
@@ -20,20 +17,10 @@ lazy_static! {
         if !Path::new("TptHandRank.dat").exists() {
             generate_data_file(Path::new("TptHandRank.dat")).ok().unwrap();
         }
-        TwoPlusTwoEvaluator::with_data_file("TptHandRank.dat")
+        TwoPlusTwoEvaluator::from_file("TptHandRank.dat").unwrap()
     };
 }
 
-fn cards_from_str(s: &str) -> Vec<Card> {
-    let mut ret: Vec<Card> = Vec::new();
-    let chars = s.chars();
-    for i in 0..chars.count()/2 {
-        let cs = s.get(i*2..i*2+2).unwrap();
-        ret.push(Card::from_str(cs).ok().unwrap());
-    }
-    return ret;
-}
-
 macro_rules! cards_case {
     ($name:ident, $evaluator:ident) => {
         #[test]
@@ -50,6 +37,7 @@ macro_rules! cards_case {
                 ("AsAdAh7c8hKd", CardsType::Three, vec!["A", "A","A","K", "8"]),
 
                 ("AsAdAh8c8hKd", CardsType::Full, vec!["A", "A","A","8","8"]),
+                ("9c9s8c6c8s6s9d", CardsType::Full, vec!["9", "9","9","8","8"]),
                 ("AsAdKh8c8hKd", CardsType::Pair2, vec!["A", "A","K","K", "8"]),
                 ("As7dKh8c3h2d9c", CardsType::High, vec!["A", "K", "9", "8", "7"]),
                 ("As7dKh2c3h2d", CardsType::Pair, vec!["2", "2","A", "K", "7"]),
@@ -57,11 +45,11 @@ macro_rules! cards_case {
                 ("QsAdKhJcTh9d", CardsType::Straight, vec!["A","K","Q","J","T"]),
                 ("2sAd3h4c5h3d", CardsType::Straight, vec!["5","4","3","2","A"]),
             ].iter(){
-                let cmp_value = $evaluator.simple_eval(&cards_from_str(cards));
+                let cmp_value = $evaluator.simple_eval(&parse_cards::<Vec<Card>>(cards).unwrap());
                 let (tp, tp_cards) = $evaluator.unpack_eval_value(cmp_value).unwrap();
                 assert_eq!(tp, *cards_type);
                 assert_eq!(tp_cards.iter()
-                           .map(|&x| x.to_string().to_string())
+                           .map(|&x| x.as_str().to_string())
                            .collect::<Vec<String>>(),
                            major_cards.iter().map(|&x| x.to_string()).collect::<Vec<String>>());
             }
@@ -72,3 +60,146 @@ macro_rules! cards_case {
 cards_case!(native_cards_cases, NATIVE_EVALUATOR);
 cards_case!(cactuskev_cards_cases, CACTUSKEV_EVALUATOR);
 cards_case!(twoplustwo_cards_cases, TWOPLUSTWO_EVALUATOR);
+
+#[test]
+fn hand_rank_same_for_all_evaluators() {
+    for cards in ["As7dKh8c3h2d9c", "QsAsKsJsTs9s", "2sAs3s4s5s8s", "AsAdAh8c8hKd",
+                  "AsAdKh8c8hKd", "2sAd3h4c5h3d", "7h5d4c3s2h", "KsKdKhKcAd"].iter() {
+        let cards: Vec<Card> = parse_cards(cards).unwrap();
+        let rank = NATIVE_EVALUATOR.simple_eval_rank(&cards).unwrap();
+        assert_eq!(Some(rank), CACTUSKEV_EVALUATOR.simple_eval_rank(&cards));
+        assert_eq!(Some(rank), TWOPLUSTWO_EVALUATOR.simple_eval_rank(&cards));
+    }
+    let worst = NATIVE_EVALUATOR.simple_eval_rank(&parse_cards::<Vec<Card>>("7h5d4c3s2h").unwrap()).unwrap();
+    assert_eq!(worst, pokerlib::HandRank::MIN);
+}
+
+fn incremental_same_as_eval<E: IncrementalEvaluator>(evaluator: &E) where E::CardType: Copy {
+    use pokerlib::deck::Deck;
+    use pokerlib::rng::SeededRng;
+
+    let mut rng = SeededRng::new(19);
+    for _ in 0..50_000 {
+        let mut deck = Deck::new();
+        deck.shuffle(&mut rng);
+        let cards: Vec<E::CardType> = deck.deal(7).unwrap().iter().map(|c| evaluator.make_card(c)).collect();
+        let mut state = evaluator.start();
+        for (i, &card) in cards.iter().enumerate() {
+            state = evaluator.add_card(state, card);
+            if i >= 4 {
+                assert_eq!(evaluator.finish(state), evaluator.eval(&cards[..=i]));
+            }
+        }
+    }
+    // 三条加两对, 同花中的顺子
+    for cards in ["9c9s8c6c8s6s9d", "2sAs3s4s5s8s", "AsAdKh8c8hKd", "KsKdKhKcAd2c"].iter() {
+        let cards: Vec<E::CardType> = parse_cards::<Vec<Card>>(cards).unwrap().iter().map(|c| evaluator.make_card(c)).collect();
+        let state = cards.iter().fold(evaluator.start(), |state, &card| evaluator.add_card(state, card));
+        assert_eq!(evaluator.finish(state), evaluator.eval(&cards));
+    }
+}
+
+#[test]
+fn native_incremental_eval() {
+    incremental_same_as_eval(&*NATIVE_EVALUATOR);
+}
+
+#[test]
+fn twoplustwo_incremental_eval() {
+    incremental_same_as_eval(&*TWOPLUSTWO_EVALUATOR);
+}
+
+macro_rules! batch_case {
+    ($name:ident, $evaluator:ident) => {
+        #[test]
+        fn $name() {
+            use pokerlib::deck::Deck;
+            use pokerlib::rng::SeededRng;
+
+            let mut rng = SeededRng::new(20);
+            for &n in [5, 6, 7].iter() {
+                // 不是批大小的整数倍, 覆盖最后不满的一批
+                let cards: Vec<u32> = (0..1000).flat_map(|_| {
+                    let mut deck = Deck::new();
+                    deck.shuffle(&mut rng);
+                    deck.deal(n).unwrap()
+                }).map(|c| $evaluator.make_card(&c)).collect();
+                let mut out = vec![0; 1000];
+                $evaluator.eval_batch(&cards, n, &mut out);
+                for (hand, &value) in cards.chunks(n).zip(out.iter()) {
+                    assert_eq!(value, $evaluator.eval(hand));
+                }
+            }
+            // 随机很少出现的牌型
+            let hands = ["9c9s8c6c8s6s9d", "2sAs3s4s5s8s9h", "AsAdKh8c8hKd2c", "KsKdKhKcAd2c3c",
+                         "Ts9s8s7s6s5s4s", "5d4h3c2sAd9h9c", "AsAdAhKsKdKhQc", "2c2d2h3c3d3h4s"];
+            let cards: Vec<u32> = hands.iter()
+                .flat_map(|s| parse_cards::<Vec<Card>>(s).unwrap())
+                .map(|c| $evaluator.make_card(&c)).collect();
+            let mut out = vec![0; hands.len()];
+            $evaluator.eval_batch(&cards, 7, &mut out);
+            for (hand, &value) in cards.chunks(7).zip(out.iter()) {
+                assert_eq!(value, $evaluator.eval(hand));
+            }
+        }
+    };
+}
+
+// 每种 5 张牌都和 eval 一致
+#[test]
+fn native_eval_batch_all_five_cards() {
+    use pokerlib::tools::combination::CombinationIter;
+
+    let cards: Vec<u32> = Card::one_desk_cards().iter().map(|c| NATIVE_EVALUATOR.make_card(c)).collect();
+    let mut hands = Vec::with_capacity(2_598_960 * 5);
+    let mut indexes = [0; 5];
+    let mut it = CombinationIter::new(52, 5);
+    while it.move_next(&mut indexes) {
+        hands.extend(indexes.iter().map(|&i| cards[i]));
+    }
+    let mut out = vec![0; hands.len() / 5];
+    NATIVE_EVALUATOR.eval_batch(&hands, 5, &mut out);
+    for (hand, &value) in hands.chunks(5).zip(out.iter()) {
+        assert_eq!(value, NATIVE_EVALUATOR.eval(hand));
+    }
+}
+
+batch_case!(native_eval_batch, NATIVE_EVALUATOR);
+batch_case!(cactuskev_eval_batch, CACTUSKEV_EVALUATOR);
+batch_case!(twoplustwo_eval_batch, TWOPLUSTWO_EVALUATOR);
+
+#[test]
+fn twoplustwo_invalid_data_file() {
+    use pokerlib::evaluator::twoplustwo::DataFileError;
+
+    let path = std::env::temp_dir().join("pokerlib_truncated_hand_rank.dat");
+    std::fs::write(&path, [0u8; 1024]).unwrap();
+    match TwoPlusTwoEvaluator::from_file(&path) {
+        Err(DataFileError::InvalidSize{actual, ..}) => assert_eq!(actual, 1024),
+        _ => panic!("truncated data file should be rejected"),
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(TwoPlusTwoEvaluator::from_file("not_exists.dat"), Err(DataFileError::Io(_))));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn twoplustwo_mmap_data_file() {
+    lazy_static::initialize(&TWOPLUSTWO_EVALUATOR);
+    let mapped = TwoPlusTwoEvaluator::from_file_mmap("TptHandRank.dat").unwrap();
+    for cards in ["As7dKh8c3h2d9c", "QsAsKsJsTs9s", "AsAdKh8c8hKd"].iter() {
+        let cards: Vec<Card> = parse_cards(cards).unwrap();
+        assert_eq!(mapped.simple_eval(&cards), TWOPLUSTWO_EVALUATOR.simple_eval(&cards));
+    }
+}
+
+#[cfg(feature = "embedded")]
+#[test]
+fn twoplustwo_embedded_table() {
+    let embedded = TwoPlusTwoEvaluator::embedded();
+    for cards in ["As7dKh8c3h2d9c", "QsAsKsJsTs9s", "AsAdKh8c8hKd", "2sAd3h4c5h3d"].iter() {
+        let cards: Vec<Card> = parse_cards(cards).unwrap();
+        assert_eq!(embedded.simple_eval(&cards), TWOPLUSTWO_EVALUATOR.simple_eval(&cards));
+    }
+}